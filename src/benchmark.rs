@@ -0,0 +1,145 @@
+// Wordroute – A word game
+// Copyright (C) 2024  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Times the word finder and the word search on all of the puzzles in
+// puzzles.txt
+
+#[allow(dead_code)]
+mod grid;
+#[allow(dead_code)]
+mod build;
+#[allow(dead_code)]
+mod dictionary;
+#[allow(dead_code)]
+mod layered_dictionary;
+mod directions;
+#[allow(dead_code)]
+mod word_finder;
+#[allow(dead_code)]
+mod counts;
+#[allow(dead_code)]
+mod puzzle_data;
+#[allow(dead_code)]
+mod shavicode;
+
+use std::hint::black_box;
+use std::time::Instant;
+use std::process::ExitCode;
+use std::num::NonZeroUsize;
+use puzzle_data::PuzzleData;
+use dictionary::Dictionary;
+use layered_dictionary::LayeredDictionary;
+
+fn time<F: FnMut()>(name: &str, iterations: u32, mut func: F) {
+    let start = Instant::now();
+
+    for _ in 0..iterations {
+        func();
+    }
+
+    let elapsed = start.elapsed();
+
+    println!(
+        "{:<16} {:>10.1} µs/iter",
+        name,
+        elapsed.as_secs_f64() * 1_000_000.0 / iterations as f64,
+    );
+}
+
+fn main() -> ExitCode {
+    let mut puzzles = Vec::new();
+
+    for (line_num, line) in include_str!("../puzzles.txt").lines().enumerate() {
+        match line.parse::<PuzzleData>() {
+            Ok(p) => puzzles.push(p),
+            Err(e) => {
+                eprintln!("puzzles.txt:{}: {}", line_num + 1, e);
+                return ExitCode::FAILURE;
+            },
+        }
+    }
+
+    let all_words = puzzles.iter()
+        .flat_map(|puzzle| puzzle.words.iter().map(|(word, _)| word.as_str()))
+        .collect::<Vec<_>>();
+
+    let mut finder = word_finder::Finder::new();
+    let mut route = Vec::new();
+
+    time("find", 100, || {
+        for puzzle in puzzles.iter() {
+            for (word, _) in puzzle.words.iter() {
+                route.clear();
+                black_box(finder.find(&puzzle.grid, word, &mut route));
+            }
+        }
+    });
+
+    // Most of the words from the other puzzles won’t be in the grid
+    time("find misses", 100, || {
+        for puzzle in puzzles.iter() {
+            for word in all_words.iter() {
+                route.clear();
+                black_box(finder.find(&puzzle.grid, word, &mut route));
+            }
+        }
+    });
+
+    time("count_visits", 100, || {
+        for puzzle in puzzles.iter() {
+            black_box(build::count_visits(
+                &puzzle.grid,
+                puzzle.words.iter().map(|(word, _)| word),
+            ));
+        }
+    });
+
+    let dictionary: LayeredDictionary =
+        Dictionary::from_words(all_words.iter()).into();
+
+    time("search_words", 100, || {
+        for puzzle in puzzles.iter() {
+            black_box(build::search_words(&puzzle.grid, &dictionary, 4));
+        }
+    });
+
+    let grids = puzzles.iter().map(|puzzle| &puzzle.grid).collect::<Vec<_>>();
+
+    time("search_grids", 100, || {
+        black_box(build::search_grids(
+            &grids,
+            &dictionary,
+            4,
+            NonZeroUsize::MIN,
+        ));
+    });
+
+    if let Some(n_threads) = std::thread::available_parallelism()
+        .ok()
+        .filter(|n| n.get() > 1)
+    {
+        time(&format!("search_grids ({} threads)", n_threads), 100, || {
+            black_box(build::search_grids(
+                &grids,
+                &dictionary,
+                4,
+                n_threads,
+            ));
+        });
+    }
+
+    ExitCode::SUCCESS
+}
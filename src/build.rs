@@ -15,64 +15,79 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::grid::Grid;
-use super::dictionary;
-use super::directions::{self, N_DIRECTIONS};
+use super::layered_dictionary::{LayeredDictionary, LayeredWalker};
 use super::counts::GridCounts;
 use super::word_finder;
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::num::NonZeroUsize;
+use std::cmp::Reverse;
 
 struct StackEntry<'a> {
     x: u32,
     y: u32,
-    walker: dictionary::Walker<'a>,
+    walker: LayeredWalker<'a>,
+    // Number of letters in the word before this tile
+    length: usize,
     next_direction: u8,
 }
 
+// Step the walker through all of the letters on a tile
+fn step_tile<'a>(
+    walker: &LayeredWalker<'a>,
+    tile: &str,
+) -> Option<LayeredWalker<'a>> {
+    let mut letters = tile.chars();
+    let walker = walker.step(letters.next()?)?;
+
+    letters.try_fold(walker, |walker, letter| walker.step(letter))
+}
+
+// The visited slice must be all false. It will be left that way after
+// the search.
 fn search_from_pos(
     grid: &Grid,
-    dictionary: &dictionary::Dictionary,
+    walker: LayeredWalker,
     minimum_length: usize,
     x: u32,
     y: u32,
+    visited: &mut [bool],
     word_list: &mut HashSet<String>,
 ) {
-    let Some(walker) = dictionary::Walker::new(dictionary)
-    else {
-        return;
-    };
-
     let mut stack = vec![StackEntry {
         x,
         y,
         walker,
+        length: 0,
         next_direction: 0,
     }];
 
-    let mut visited = vec![false; (grid.width() * grid.height()) as usize];
+    let topology = grid.topology();
 
     while let Some(mut entry) = stack.pop() {
         if entry.next_direction == 0 &&
             (entry.x >= grid.width() ||
              entry.y >= grid.height() ||
              visited[(entry.y * grid.width() + entry.x) as usize] ||
-             entry.walker.step(grid.at(entry.x, entry.y)).is_none())
+             step_tile(&entry.walker, grid.at(entry.x, entry.y)).is_none())
         {
             // Backtrack
             while let Some(entry) = stack.pop() {
                 visited[(entry.y * grid.width() + entry.x) as usize] = false;
 
-                if entry.next_direction < N_DIRECTIONS {
+                if entry.next_direction < topology.n_directions() {
                     stack.push(entry);
                     break;
                 }
             }
         } else {
-            let letter = grid.at(entry.x, entry.y);
-            let next_walker = entry.walker.step(letter).unwrap();
+            let tile = grid.at(entry.x, entry.y);
+            let next_walker = step_tile(&entry.walker, tile).unwrap();
 
             visited[(entry.y * grid.width() + entry.x) as usize] = true;
 
-            let word_length = stack.len() + 1;
+            // The length counts letters rather than tiles
+            let word_length = entry.length + tile.chars().count();
 
             if entry.next_direction == 0 &&
                 word_length >= minimum_length &&
@@ -81,11 +96,11 @@ fn search_from_pos(
                 let mut word = stack.iter().map(|entry| {
                     grid.at(entry.x, entry.y)
                 }).collect::<String>();
-                word.push(letter);
+                word.push_str(tile);
                 word_list.insert(word);
             }
 
-            let next_pos = directions::step(
+            let next_pos = topology.step(
                 entry.x,
                 entry.y,
                 entry.next_direction,
@@ -95,6 +110,7 @@ fn search_from_pos(
                 x: next_pos.0,
                 y: next_pos.1,
                 walker: next_walker,
+                length: word_length,
                 next_direction: 0,
             };
 
@@ -106,28 +122,112 @@ fn search_from_pos(
     }
 }
 
-pub fn search_words(
-    grid: &Grid,
-    dictionary: &dictionary::Dictionary,
+// Runs the jobs until there are none left and returns the words that
+// were found for each grid
+fn run_jobs(
+    grids: &[&Grid],
+    jobs: &[(usize, u32, u32)],
+    next_job: &AtomicUsize,
+    root: &LayeredWalker,
     minimum_length: usize,
-) -> HashSet<String> {
-    let mut word_list = HashSet::new();
+) -> Vec<HashSet<String>> {
+    let mut results = vec![HashSet::new(); grids.len()];
+    let mut visited = Vec::new();
 
-    for y in 0..grid.height() {
-        for x in 0..grid.width() {
-            search_from_pos(
-                grid,
-                dictionary,
-                minimum_length,
-                x, y,
-                &mut word_list,
-            );
+    loop {
+        let job = next_job.fetch_add(1, Ordering::Relaxed);
+
+        let Some(&(grid_num, x, y)) = jobs.get(job)
+        else {
+            break;
+        };
+
+        let grid = grids[grid_num];
+
+        visited.clear();
+        visited.resize((grid.width() * grid.height()) as usize, false);
+
+        search_from_pos(
+            grid,
+            root.clone(),
+            minimum_length,
+            x, y,
+            &mut visited,
+            &mut results[grid_num],
+        );
+    }
+
+    results
+}
+
+// Searches for the words in several grids at once. Every start
+// position of every grid is a separate job and the jobs are shared
+// out between the threads. The result has the words for each grid in
+// the same order as the grids.
+pub fn search_grids(
+    grids: &[&Grid],
+    dictionary: &LayeredDictionary,
+    minimum_length: usize,
+    n_threads: NonZeroUsize,
+) -> Vec<HashSet<String>> {
+    let Some(root) = LayeredWalker::new(dictionary)
+    else {
+        return vec![HashSet::new(); grids.len()];
+    };
+
+    let mut jobs = Vec::new();
+
+    for (grid_num, grid) in grids.iter().enumerate() {
+        for (letter, positions) in grid.letter_positions() {
+            if root.step(letter).is_some() {
+                jobs.extend(positions.iter().map(|&(x, y)| (grid_num, x, y)));
+            }
         }
     }
 
-    word_list
+    let next_job = AtomicUsize::new(0);
+
+    // Starting the threads isn’t worth it if there is only one job
+    // to share out
+    if n_threads.get() == 1 || jobs.len() <= 1 {
+        return run_jobs(grids, &jobs, &next_job, &root, minimum_length);
+    }
+
+    let mut results = vec![HashSet::new(); grids.len()];
+
+    std::thread::scope(|scope| {
+        let threads = (0..n_threads.get().min(jobs.len())).map(|_| {
+            scope.spawn(|| {
+                run_jobs(grids, &jobs, &next_job, &root, minimum_length)
+            })
+        }).collect::<Vec<_>>();
+
+        for thread in threads {
+            for (result, words) in results.iter_mut()
+                .zip(thread.join().unwrap())
+            {
+                result.extend(words);
+            }
+        }
+    });
+
+    results
 }
 
+// Searches for the words in a single grid on the current thread
+pub fn search_words(
+    grid: &Grid,
+    dictionary: &LayeredDictionary,
+    minimum_length: usize,
+) -> HashSet<String> {
+    search_grids(&[grid], dictionary, minimum_length, NonZeroUsize::MIN)
+        .pop()
+        .unwrap()
+}
+
+// If a word can be spelled with more than one route then only the
+// first route that the word finder returns is counted. This is the
+// same route that the game uses so the counts will match.
 pub fn count_visits<I, T>(
     grid: &Grid,
     words: I,
@@ -145,24 +245,205 @@ pub fn count_visits<I, T>(
         let (mut x, mut y) =
             finder.find(grid, word.as_ref(), &mut steps).unwrap();
 
+        // The counts only have room for a u8 so they stop at 255
+        // instead of wrapping around to zero
         let start = counts.at_mut(x, y);
-        start.starts += 1;
-        start.visits += 1;
+        start.starts = start.starts.saturating_add(1);
+        start.visits = start.visits.saturating_add(1);
 
         for &step in steps.iter() {
-            (x, y) = directions::step(x, y, step);
-            counts.at_mut(x, y).visits += 1;
+            (x, y) = grid.topology().step(x, y, step);
+            let tile = counts.at_mut(x, y);
+            tile.visits = tile.visits.saturating_add(1);
         }
     }
 
     counts
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stats {
+    pub n_words: usize,
+    pub longest_word: usize,
+    // Number of tiles that aren’t visited by any word
+    pub unused_tiles: usize,
+}
+
+impl Stats {
+    pub fn new<I, T>(grid: &Grid, words: I) -> Stats
+        where I: IntoIterator<Item = T>,
+              T: AsRef<str>
+    {
+        let words = words.into_iter().collect::<Vec<_>>();
+        let counts = count_visits(grid, words.iter());
+
+        Stats {
+            n_words: words.len(),
+            longest_word: words.iter()
+                .map(|word| word.as_ref().chars().count())
+                .max()
+                .unwrap_or(0),
+            unused_tiles: unused_tiles(grid, &counts).len(),
+        }
+    }
+}
+
+// Returns the positions of the tiles that aren’t visited by any of
+// the words that were counted, in reading order
+pub fn unused_tiles(grid: &Grid, counts: &GridCounts) -> Vec<(u32, u32)> {
+    (0..grid.height()).flat_map(|y| {
+        (0..grid.width()).map(move |x| (x, y))
+    }).filter(|&(x, y)| {
+        grid.at(x, y) != "." && counts.at(x, y).visits == 0
+    }).collect()
+}
+
+pub struct Suggestion {
+    pub x: u32,
+    pub y: u32,
+    pub letter: char,
+    pub stats: Stats,
+}
+
+// Tries replacing each tile with every one of the letters. Returns
+// the stats of the current grid along with the changes sorted so that
+// the ones with the fewest unused tiles come first, then the most
+// words and then the longest word. Only the words for which is_normal
+// returns true are counted.
+pub fn suggest_changes<F>(
+    grid: &Grid,
+    dictionary: &LayeredDictionary,
+    minimum_length: usize,
+    n_threads: NonZeroUsize,
+    letters: &[char],
+    is_normal: F,
+) -> (Stats, Vec<Suggestion>)
+    where F: Fn(&str) -> bool
+{
+    let mut changes = Vec::new();
+
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            let tile = grid.at(x, y);
+
+            if tile == "." {
+                continue;
+            }
+
+            for &letter in letters.iter() {
+                if tile.chars().ne(std::iter::once(letter)) {
+                    changes.push((x, y, letter));
+                }
+            }
+        }
+    }
+
+    let mut grids = changes.iter().map(|&(x, y, letter)| {
+        grid.with_tile(x, y, &letter.to_string())
+    }).collect::<Vec<_>>();
+
+    // Search the original grid along with the changes so that they
+    // can be compared
+    grids.push(grid.clone());
+
+    let mut results = search_grids(
+        &grids.iter().collect::<Vec<_>>(),
+        dictionary,
+        minimum_length,
+        n_threads,
+    ).into_iter().zip(grids.iter()).map(|(words, grid)| {
+        Stats::new(grid, words.iter().filter(|word| is_normal(word)))
+    }).collect::<Vec<_>>();
+
+    let current = results.pop().unwrap();
+
+    let mut suggestions = changes.into_iter()
+        .zip(results)
+        .map(|((x, y, letter), stats)| Suggestion { x, y, letter, stats })
+        .collect::<Vec<_>>();
+
+    suggestions.sort_by_key(|s| {
+        (
+            s.stats.unused_tiles,
+            Reverse(s.stats.n_words),
+            Reverse(s.stats.longest_word),
+            s.y,
+            s.x,
+            s.letter,
+        )
+    });
+
+    (current, suggestions)
+}
+
+// Removes each tile in turn and returns the normal words that can no
+// longer be found without it. The tiles that lose the most words come
+// first.
+pub fn tile_criticality(
+    grid: &Grid,
+    dictionary: &LayeredDictionary,
+    minimum_length: usize,
+    n_threads: NonZeroUsize,
+    normal_words: &[&str],
+) -> Vec<((u32, u32), Vec<String>)> {
+    let tiles = (0..grid.height()).flat_map(|y| {
+        (0..grid.width()).map(move |x| (x, y))
+    }).filter(|&(x, y)| grid.at(x, y) != ".").collect::<Vec<_>>();
+
+    let grids = tiles.iter().map(|&(x, y)| {
+        grid.with_tile(x, y, ".")
+    }).collect::<Vec<_>>();
+
+    let results = search_grids(
+        &grids.iter().collect::<Vec<_>>(),
+        dictionary,
+        minimum_length,
+        n_threads,
+    );
+
+    let mut lost_words = tiles.into_iter().zip(results).map(|(tile, words)| {
+        let lost = normal_words.iter()
+            .filter(|&&word| !words.contains(word))
+            .map(|&word| word.to_string())
+            .collect::<Vec<_>>();
+        (tile, lost)
+    }).collect::<Vec<_>>();
+
+    // The sort is stable so tiles that lose the same number of words
+    // stay in reading order
+    lost_words.sort_by_key(|(_, lost)| Reverse(lost.len()));
+
+    lost_words
+}
+
+// Whether the word has a route that visits every tile that isn’t
+// blank
+pub fn uses_all_tiles(grid: &Grid, word: &str) -> bool {
+    let n_tiles = (0..grid.height()).flat_map(|y| {
+        (0..grid.width()).map(move |x| (x, y))
+    }).filter(|&(x, y)| grid.at(x, y) != ".").count();
+
+    // Every tile has at least one letter
+    if word.chars().count() < n_tiles {
+        return false;
+    }
+
+    let mut found = false;
+
+    word_finder::Finder::new().for_each_route(grid, word, |_, steps| {
+        found = steps.len() + 1 == n_tiles;
+        !found
+    });
+
+    found
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use super::super::dictionary::Dictionary;
 
-    fn make_dictionary() -> dictionary::Dictionary {
+    fn make_dictionary() -> LayeredDictionary {
         // Dictonary with the words 𐑕𐑑𐑨𐑓𐑑 and 𐑒𐑨𐑚
         static DICTIONARY_BYTES: [u8; 57] = [
             0x00, 0x01, b'*',
@@ -178,15 +459,14 @@ mod test {
             0x00, 0x00, b'\0',
         ];
 
-        dictionary::Dictionary::new(Box::new(DICTIONARY_BYTES.clone()))
+        Dictionary::new(Box::new(DICTIONARY_BYTES)).into()
     }
 
     fn search(grid: &str, minimum_length: usize) -> Vec<String> {
-        let mut words = search_words(
-            &Grid::new(grid).unwrap(),
-            &make_dictionary(),
-            minimum_length,
-        ).into_iter().collect::<Vec<_>>();
+        let grid = Grid::new(grid).unwrap();
+        let mut words = search_words(&grid, &make_dictionary(), minimum_length)
+            .into_iter()
+            .collect::<Vec<_>>();
 
         words.sort_unstable();
 
@@ -246,58 +526,185 @@ mod test {
 
         assert_eq!(
             &search(
-                " 𐑕 x x\
-                 : 𐑑 x x\
-                 :x 𐑨 x\
-                 : x 𐑓 x\
-                 :x x 𐑑",
+                " 𐑕 𐑿 𐑿\
+                 : 𐑑 𐑿 𐑿\
+                 :𐑿 𐑨 𐑿\
+                 : 𐑿 𐑓 𐑿\
+                 :𐑿 𐑿 𐑑",
                 3,
             ),
             &["𐑕𐑑𐑨𐑓𐑑"]);
         assert_eq!(
             &search(
-                " x x 𐑕\
-                 : x 𐑑 x\
-                 :x 𐑨 x\
-                 : 𐑓 x x\
-                 :𐑑 x x",
+                " 𐑿 𐑿 𐑕\
+                 : 𐑿 𐑑 𐑿\
+                 :𐑿 𐑨 𐑿\
+                 : 𐑓 𐑿 𐑿\
+                 :𐑑 𐑿 𐑿",
                 3,
             ),
             &["𐑕𐑑𐑨𐑓𐑑"]);
 
         assert_eq!(
             &search(
-                " 𐑑 x x\
-                 : 𐑓 x x\
-                 :x 𐑨 x\
-                 : x 𐑑 x\
-                 :x x 𐑕",
+                " 𐑑 𐑿 𐑿\
+                 : 𐑓 𐑿 𐑿\
+                 :𐑿 𐑨 𐑿\
+                 : 𐑿 𐑑 𐑿\
+                 :𐑿 𐑿 𐑕",
                 3,
             ),
             &["𐑕𐑑𐑨𐑓𐑑"]);
         assert_eq!(
             &search(
-                " x x 𐑑\
-                 : x 𐑓 x\
-                 :x 𐑨 x\
-                 : 𐑑 x x\
-                 :𐑕 x x",
+                " 𐑿 𐑿 𐑑\
+                 : 𐑿 𐑓 𐑿\
+                 :𐑿 𐑨 𐑿\
+                 : 𐑑 𐑿 𐑿\
+                 :𐑕 𐑿 𐑿",
                 3,
             ),
             &["𐑕𐑑𐑨𐑓𐑑"]);
     }
 
+    #[test]
+    fn thread_counts() {
+        let grids = [
+            "𐑒𐑨𐑚",
+            " 𐑒 𐑨 𐑚 𐑕\
+             : 𐑑 𐑓 𐑨 𐑑",
+            " . 𐑕 𐑑\
+             : 𐑿 𐑓 𐑨",
+            "𐑑𐑓𐑨𐑑𐑕",
+        ].map(|grid| Grid::new(grid).unwrap());
+        let grid_refs = grids.iter().collect::<Vec<_>>();
+        let dictionary = make_dictionary();
+
+        let expected = search_grids(
+            &grid_refs,
+            &dictionary,
+            3,
+            NonZeroUsize::MIN,
+        );
+
+        assert_eq!(expected.len(), grids.len());
+        assert!(expected[0].contains("𐑒𐑨𐑚"));
+        assert!(expected[1].contains("𐑕𐑑𐑨𐑓𐑑"));
+        assert!(expected[3].contains("𐑕𐑑𐑨𐑓𐑑"));
+
+        for n_threads in [2, 7] {
+            let results = search_grids(
+                &grid_refs,
+                &dictionary,
+                3,
+                NonZeroUsize::new(n_threads).unwrap(),
+            );
+
+            assert_eq!(results, expected);
+        }
+
+        for (grid, words) in grids.iter().zip(expected.iter()) {
+            assert_eq!(&search_words(grid, &dictionary, 3), words);
+        }
+
+        assert!(search_grids(
+            &[],
+            &dictionary,
+            3,
+            NonZeroUsize::new(4).unwrap(),
+        ).is_empty());
+    }
+
+    #[test]
+    fn single_grid_threads() {
+        let grid = Grid::new(
+            " 𐑒 𐑨 𐑚 𐑕\
+             : 𐑑 𐑓 𐑨 𐑑",
+        ).unwrap();
+        let dictionary = make_dictionary();
+
+        let expected = search_grids(
+            &[&grid],
+            &dictionary,
+            3,
+            NonZeroUsize::MIN,
+        );
+
+        assert!(expected[0].contains("𐑕𐑑𐑨𐑓𐑑"));
+
+        for n_threads in [2, 7] {
+            let results = search_grids(
+                &[&grid],
+                &dictionary,
+                3,
+                NonZeroUsize::new(n_threads).unwrap(),
+            );
+
+            assert_eq!(results, expected);
+        }
+    }
+
+    #[test]
+    fn ambiguous_visits() {
+        // 𐑕𐑑𐑨𐑓𐑑 can end on either of the 𐑑s at the bottom
+        let grid = Grid::new(
+            " 𐑕 𐑑 𐑿\
+             : 𐑨 𐑓 𐑑\
+             :𐑿 𐑑 𐑿"
+        ).unwrap();
+
+        let mut finder = word_finder::Finder::new();
+        assert_eq!(finder.count_routes(&grid, "𐑕𐑑𐑨𐑓𐑑", usize::MAX), 2);
+
+        // Only the first route is counted
+        let counts = count_visits(&grid, ["𐑕𐑑𐑨𐑓𐑑"]);
+        assert_eq!(counts.at(2, 1).visits, 1);
+        assert_eq!(counts.at(1, 2).visits, 0);
+    }
+
+    #[test]
+    fn square() {
+        let grid = " 𐑕 𐑑 𐑚\
+                    : 𐑿 𐑨 𐑓\
+                    : 𐑒 𐑿 𐑑";
+
+        // 𐑒𐑨𐑚 is only possible with the diagonal directions
+        assert_eq!(&search(grid, 3), &["𐑕𐑑𐑨𐑓𐑑"]);
+        assert_eq!(
+            &search(&format!("#{}", grid), 3),
+            &["𐑒𐑨𐑚", "𐑕𐑑𐑨𐑓𐑑"],
+        );
+    }
+
     #[test]
     fn minimum_length() {
         assert!(&search("𐑒𐑨𐑚", 4).is_empty());
         assert_eq!(&search("𐑒𐑨𐑚", 3), &["𐑒𐑨𐑚"]);
     }
 
+    #[test]
+    fn multi_letter_tiles() {
+        assert_eq!(&search("[𐑕𐑑] 𐑨 [𐑓𐑑]", 3), &["𐑕𐑑𐑨𐑓𐑑"]);
+        assert_eq!(&search("𐑒 [𐑨𐑚]", 3), &["𐑒𐑨𐑚"]);
+        // The minimum length counts letters, not tiles
+        assert_eq!(&search("[𐑕𐑑] 𐑨 [𐑓𐑑]", 5), &["𐑕𐑑𐑨𐑓𐑑"]);
+        assert!(&search("[𐑒𐑨𐑚]", 4).is_empty());
+        assert_eq!(&search("[𐑒𐑨𐑚]", 3), &["𐑒𐑨𐑚"]);
+        // Tiles that only partially match don’t count
+        assert!(&search("[𐑒𐑨] [𐑚𐑚]", 3).is_empty());
+
+        let grid = Grid::new("[𐑕𐑑] 𐑨 [𐑓𐑑]").unwrap();
+        let counts = count_visits(&grid, ["𐑕𐑑𐑨𐑓𐑑"]);
+        assert_eq!(counts.at(0, 0).starts, 1);
+        assert_eq!(counts.at(1, 0).visits, 1);
+        assert_eq!(counts.at(2, 0).visits, 1);
+    }
+
     #[test]
     fn visits() {
         let grid = Grid::new(
-            " 𐑕 𐑑 x\
-             : 𐑨 𐑓 x\
+            " 𐑕 𐑑 𐑿\
+             : 𐑨 𐑓 𐑿\
              :𐑒 𐑚 𐑑"
         ).unwrap();
 
@@ -327,4 +734,132 @@ mod test {
         assert_eq!(counts.at(2, 2).starts, 0);
         assert_eq!(counts.at(2, 2).visits, 1);
     }
+
+    #[test]
+    fn saturated_visits() {
+        let grid = Grid::new("𐑒𐑨𐑚").unwrap();
+
+        let counts = count_visits(&grid, std::iter::repeat_n("𐑒𐑨𐑚", 300));
+
+        assert_eq!(counts.at(0, 0).starts, 255);
+        assert_eq!(counts.at(0, 0).visits, 255);
+        assert_eq!(counts.at(1, 0).starts, 0);
+        assert_eq!(counts.at(1, 0).visits, 255);
+        assert_eq!(counts.at(2, 0).visits, 255);
+    }
+
+    #[test]
+    fn unused() {
+        let grid = Grid::new(
+            " 𐑒 𐑨 𐑚 𐑿\
+             : . 𐑑 . 𐑕"
+        ).unwrap();
+
+        let counts = count_visits(&grid, ["𐑒𐑨𐑚"]);
+        assert_eq!(&unused_tiles(&grid, &counts), &[(3, 0), (1, 1), (3, 1)]);
+
+        let counts = count_visits(&grid, std::iter::empty::<&str>());
+        assert_eq!(unused_tiles(&grid, &counts).len(), 6);
+    }
+
+    #[test]
+    fn stats() {
+        let grid = Grid::new(
+            " 𐑒 𐑨 𐑚 𐑿\
+             : 𐑑 . . ."
+        ).unwrap();
+
+        assert_eq!(
+            Stats::new(&grid, ["𐑒𐑨𐑚", "𐑚𐑨𐑑"]),
+            Stats { n_words: 2, longest_word: 3, unused_tiles: 1 },
+        );
+        assert_eq!(
+            Stats::new(&grid, std::iter::empty::<&str>()),
+            Stats { n_words: 0, longest_word: 0, unused_tiles: 5 },
+        );
+    }
+
+    #[test]
+    fn suggestions() {
+        // The 𐑿 isn’t used by any word and changing it to 𐑑 makes
+        // 𐑚𐑨𐑑
+        let grid = Grid::new(
+            " 𐑒 𐑨 𐑚\
+             : 𐑿 . ."
+        ).unwrap();
+        let dictionary = Dictionary::from_words(["𐑒𐑨𐑚", "𐑚𐑨𐑑"]).into();
+
+        let (current, suggestions) = suggest_changes(
+            &grid,
+            &dictionary,
+            3,
+            NonZeroUsize::MIN,
+            &['𐑑', '𐑒'],
+            |_| true,
+        );
+
+        assert_eq!(
+            current,
+            Stats { n_words: 1, longest_word: 3, unused_tiles: 1 },
+        );
+
+        // Each of the four tiles can be changed to two letters,
+        // except for the 𐑒 that can only change to 𐑑
+        assert_eq!(suggestions.len(), 7);
+
+        let first = &suggestions[0];
+        assert_eq!((first.x, first.y, first.letter), (0, 1, '𐑑'));
+        assert_eq!(
+            first.stats,
+            Stats { n_words: 2, longest_word: 3, unused_tiles: 0 },
+        );
+
+        assert!(suggestions[1..].iter().all(|s| s.stats.unused_tiles > 0));
+    }
+
+    #[test]
+    fn criticality() {
+        // Every word goes through the 𐑨
+        let grid = Grid::new(
+            " 𐑒 𐑨 𐑚\
+             : 𐑑 𐑕 ."
+        ).unwrap();
+        let dictionary = Dictionary::from_words(["𐑒𐑨𐑚", "𐑑𐑨𐑕"]).into();
+
+        let tiles = tile_criticality(
+            &grid,
+            &dictionary,
+            3,
+            NonZeroUsize::MIN,
+            &["𐑒𐑨𐑚", "𐑑𐑨𐑕"],
+        );
+
+        assert_eq!(
+            tiles,
+            &[
+                ((1, 0), vec!["𐑒𐑨𐑚".to_string(), "𐑑𐑨𐑕".to_string()]),
+                ((0, 0), vec!["𐑒𐑨𐑚".to_string()]),
+                ((2, 0), vec!["𐑒𐑨𐑚".to_string()]),
+                ((0, 1), vec!["𐑑𐑨𐑕".to_string()]),
+                ((1, 1), vec!["𐑑𐑨𐑕".to_string()]),
+            ],
+        );
+    }
+
+    #[test]
+    fn all_tiles() {
+        let grid = Grid::new("#𐑒𐑨𐑚:𐑑..").unwrap();
+
+        assert!(uses_all_tiles(&grid, "𐑑𐑒𐑨𐑚"));
+        assert!(uses_all_tiles(&grid, "𐑚𐑨𐑒𐑑"));
+        assert!(!uses_all_tiles(&grid, "𐑒𐑨𐑚"));
+        assert!(!uses_all_tiles(&grid, "𐑑𐑨𐑒𐑚"));
+
+        // Multi-letter tiles mean that the word can be longer than
+        // the number of tiles
+        let grid = Grid::new("#𐑒[𐑨𐑚]:𐑑.").unwrap();
+
+        assert!(uses_all_tiles(&grid, "𐑑𐑒𐑨𐑚"));
+        assert!(!uses_all_tiles(&grid, "𐑒𐑨𐑚"));
+    }
 }
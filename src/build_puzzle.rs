@@ -15,28 +15,51 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 mod grid;
+#[allow(dead_code)]
 mod build;
+#[allow(dead_code)]
 mod dictionary;
+mod layered_dictionary;
 mod directions;
 mod word_finder;
 mod counts;
 mod puzzle_data;
 mod shavicode;
-
-use std::path::Path;
-use std::io::{BufReader, BufRead};
+mod curate;
+mod raw_terminal;
+mod puzzle_json;
+mod load;
+#[allow(dead_code)]
+mod grid_math;
+mod svg;
+
+use std::io::{BufRead, Write};
 use std::{fs, process::ExitCode, ffi::OsString};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use std::collections::{HashSet, HashMap};
+use std::num::NonZeroUsize;
 use puzzle_data::{PuzzleData, WordType};
+use layered_dictionary::LayeredDictionary;
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Code,
+    Json,
+}
 
 #[derive(Parser)]
 #[command(name = "Build")]
 struct Cli {
     #[arg(required = true, value_name = "PUZZLE")]
     puzzles: Vec<OsString>,
+    #[arg(short, long, value_name = "FILE", required = true)]
+    dictionary: Vec<OsString>,
+    /// Text file with a list of extra words to add to the dictionary
     #[arg(short, long, value_name = "FILE")]
-    dictionary: OsString,
+    add_words: Vec<OsString>,
+    /// Text file with a list of words to remove from the dictionary
+    #[arg(short, long, value_name = "FILE")]
+    remove_words: Vec<OsString>,
     #[arg(short, long, value_name = "FILE")]
     bonus_words: Vec<OsString>,
     #[arg(short = 'x', long, value_name = "FILE")]
@@ -45,21 +68,76 @@ struct Cli {
     minimum_length: usize,
     #[arg(short = 'H', long)]
     human_readable: bool,
+    /// Format to print the puzzle in. JSON prints one object per
+    /// puzzle on each line.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Format::Code,
+        conflicts_with = "human_readable",
+    )]
+    format: Format,
+    /// Build the puzzle even if some of the tiles aren’t used by any
+    /// normal words
+    #[arg(long)]
+    allow_unused_tiles: bool,
+    /// Instead of building the puzzle, try changing each tile to
+    /// every other letter and list the changes that help the most
+    #[arg(long)]
+    suggest: bool,
+    /// Instead of building the puzzle, list the normal words that
+    /// would be lost if each tile were removed
+    #[arg(long, conflicts_with = "suggest")]
+    criticality: bool,
+    /// Step through the words to choose their types, then save the
+    /// changes to the last bonus and excluded word lists
+    #[arg(
+        short,
+        long,
+        conflicts_with_all = [
+            "suggest",
+            "criticality",
+            "human_readable",
+            "format",
+        ],
+        requires_all = ["bonus_words", "excluded_words"],
+    )]
+    interactive: bool,
+    /// Make any word whose value in the dictionary is less than
+    /// VALUE into a bonus word
+    #[arg(long, value_name = "VALUE")]
+    bonus_below: Option<u32>,
+    /// Number of threads to use to search for words. Defaults to
+    /// the number of available CPUs
+    #[arg(short, long, value_name = "N")]
+    jobs: Option<NonZeroUsize>,
+    /// Also draw the grid as an SVG image and save it to FILE
+    #[arg(long, value_name = "FILE", conflicts_with = "interactive")]
+    svg: Option<OsString>,
+    /// Show the counts on the tiles of the SVG image
+    #[arg(long, requires = "svg")]
+    svg_counts: bool,
+    /// Draw the route of WORD on the SVG image
+    #[arg(long, value_name = "WORD", requires = "svg")]
+    svg_word: Option<String>,
 }
 
 fn print_grid(grid: &grid::Grid, counts: &counts::GridCounts) {
     for y in 0..grid.height() {
-        if y & 1 != 0 {
+        // Hexagonal grids have every odd row shifted right
+        let indent = grid.shape() == directions::Shape::Hexagon && y & 1 != 0;
+
+        if indent {
             print!("   ");
         }
 
         for x in 0..grid.width() {
-            print!("  {}   ", grid.at(x, y));
+            print!("  {:<4}", grid.at(x, y));
         }
 
         println!();
 
-        if y & 1 != 0 {
+        if indent {
             print!("   ");
         }
 
@@ -78,21 +156,41 @@ fn print_human_readable(
 ) {
     print_grid(&puzzle_data.grid, counts);
 
+    let grid = &puzzle_data.grid;
+    let mut finder = word_finder::Finder::new();
+    let mut any_ambiguous = false;
+
+    // Words with more than one route are marked with an asterisk
+    // because the counts will only include one of them
+    let mut marker = |word: &str| {
+        if finder.count_routes(grid, word, 2) > 1 {
+            any_ambiguous = true;
+            "*"
+        } else {
+            ""
+        }
+    };
+
     // Split the words into buckets. There will be one for each length
     // of normal word, and one for all lengths of each other type of
     // word.
 
-    let mut buckets = HashMap::new();
+    let mut buckets = HashMap::<_, Vec<String>>::new();
+    let mut featured_words = Vec::new();
 
     for (word, word_type) in puzzle_data.words.into_iter() {
-        let key = if word_type == WordType::Normal {
+        if word_type == WordType::Featured {
+            featured_words.push(word.clone());
+        }
+
+        let key = if word_type.is_normal() {
             (word.chars().count(), WordType::Normal as u8)
         } else {
             (0, word_type as u8)
         };
 
         buckets.entry(key)
-            .or_insert_with(|| Vec::new())
+            .or_default()
             .push(word);
     }
 
@@ -113,9 +211,11 @@ fn print_human_readable(
         let mut x = 0;
 
         for word in words.into_iter() {
+            let marker = marker(&word);
             let spaces = (x == 0) as usize;
+            let width = length + marker.len();
 
-            if x + spaces + length > 80 {
+            if x + spaces + width > 80 {
                 println!();
                 x = 0;
             }
@@ -124,19 +224,27 @@ fn print_human_readable(
                 print!(" ");
             }
 
-            print!("{}", word);
+            print!("{}{}", word, marker);
 
-            x += length + spaces;
+            x += width + spaces;
         }
 
         println!();
     }
 
+    if !featured_words.is_empty() {
+        println!("\nWords that use every tile\n");
+
+        for word in featured_words.into_iter() {
+            println!("{}", word);
+        }
+    }
+
     if let Some(bonus_words) = buckets.remove(&(0, WordType::Bonus as u8)) {
         println!("\nBonus words\n");
 
         for word in bonus_words.into_iter() {
-            println!("{}", word);
+            println!("{}{}", word, marker(&word));
         }
     }
 
@@ -146,64 +254,360 @@ fn print_human_readable(
         println!("\nExcluded words\n");
 
         for word in excluded_words.into_iter() {
-            println!("{}", word);
+            println!("{}{}", word, marker(&word));
         }
     }
+
+    if any_ambiguous {
+        println!("\n* The word can be made with more than one route");
+    }
 }
 
-fn read_word_list_from_file<P: AsRef<Path>>(
-    filename: P,
-    words: &mut HashSet<String>,
-) -> Result<(), std::io::Error> {
-    for line in BufReader::new(std::fs::File::open(filename)?).lines() {
-        let line = line?;
-        let line = line.trim();
+// Number of tile changes to list with --suggest
+const N_SUGGESTIONS: usize = 20;
+
+fn print_stats_change(name: &str, before: usize, after: usize) {
+    print!("{} {:+} ({})", name, after as isize - before as isize, after);
+}
+
+// Tries replacing each tile with every letter of the alphabet and
+// lists the changes with the fewest unused tiles, then the most words
+// and then the longest word
+fn print_suggestions<F>(
+    grid: &grid::Grid,
+    dictionary: &LayeredDictionary,
+    minimum_length: usize,
+    n_threads: NonZeroUsize,
+    is_normal: F,
+)
+    where F: Fn(&str) -> bool
+{
+    let letters = shavicode::all_letters().collect::<Vec<_>>();
+
+    let (current, suggestions) = build::suggest_changes(
+        grid,
+        dictionary,
+        minimum_length,
+        n_threads,
+        &letters,
+        is_normal,
+    );
+
+    println!(
+        "Currently {} words, longest word {} letters, {} unused tiles\n",
+        current.n_words,
+        current.longest_word,
+        current.unused_tiles,
+    );
+
+    for suggestion in suggestions.iter().take(N_SUGGESTIONS) {
+        print!(
+            "row {}, column {}: {} → {}: ",
+            suggestion.y + 1,
+            suggestion.x + 1,
+            grid.at(suggestion.x, suggestion.y),
+            suggestion.letter,
+        );
+        print_stats_change(
+            "words",
+            current.n_words,
+            suggestion.stats.n_words,
+        );
+        print!(", ");
+        print_stats_change(
+            "longest word",
+            current.longest_word,
+            suggestion.stats.longest_word,
+        );
+        print!(", ");
+        print_stats_change(
+            "unused tiles",
+            current.unused_tiles,
+            suggestion.stats.unused_tiles,
+        );
+        println!();
+    }
+}
 
-        if !line.is_empty() && !line.starts_with('#') {
-            words.insert(line.to_string());
+// Removes each tile in turn and lists the normal words that can no
+// longer be found without it. The tiles that lose the most words are
+// listed first.
+fn print_criticality(
+    grid: &grid::Grid,
+    dictionary: &LayeredDictionary,
+    minimum_length: usize,
+    n_threads: NonZeroUsize,
+    normal_words: &[&str],
+) {
+    let lost_words = build::tile_criticality(
+        grid,
+        dictionary,
+        minimum_length,
+        n_threads,
+        normal_words,
+    );
+
+    for ((x, y), lost) in lost_words.into_iter() {
+        println!(
+            "row {}, column {}: {}: {} of {} words",
+            y + 1,
+            x + 1,
+            grid.at(x, y),
+            lost.len(),
+            normal_words.len(),
+        );
+
+        if !lost.is_empty() {
+            println!("  {}", lost.join(" "));
         }
     }
+}
+
+fn write_word_lists(
+    filenames: &[OsString],
+    removed: &HashSet<&str>,
+    added: &[&str],
+) -> Result<(), String> {
+    let lists = filenames.iter().map(|filename| {
+        fs::read_to_string(filename)
+            .map_err(|e| format!("{}: {}", filename.to_string_lossy(), e))
+    }).collect::<Result<Vec<_>, String>>()?;
+
+    for (filename, contents) in filenames.iter()
+        .zip(curate::update_word_lists(&lists, removed, added))
+    {
+        // The lists that don’t own any of the changed words are left
+        // alone
+        let Some(contents) = contents
+        else {
+            continue;
+        };
+
+        fs::write(filename, contents)
+            .map_err(|e| format!("{}: {}", filename.to_string_lossy(), e))?;
+    }
 
     Ok(())
 }
 
-fn read_word_list<I, P>(
-    filenames: I,
-) -> Result<HashSet<String>, std::io::Error>
-    where I: IntoIterator<Item = P>,
-          P: AsRef<Path>,
+// Makes any normal word that uses every tile into a featured word
+fn feature_all_tile_words(
+    grid: &grid::Grid,
+    words: &mut [(String, WordType)],
+) {
+    for (word, word_type) in words.iter_mut() {
+        if *word_type == WordType::Normal &&
+            build::uses_all_tiles(grid, word)
+        {
+            *word_type = WordType::Featured;
+        }
+    }
+}
+
+// Lets the user choose the type of each word while showing the
+// counts, then saves the changes to the word lists and prints the
+// puzzle code
+fn curate_puzzle<F>(
+    cli: &Cli,
+    puzzle_data: PuzzleData,
+    bonus_words: &HashSet<String>,
+    excluded_words: &HashSet<String>,
+    is_rare: F,
+) -> Result<(), String>
+    where F: Fn(&str) -> bool
 {
-    let mut words = HashSet::new();
-
-    for filename in filenames {
-        read_word_list_from_file(&filename, &mut words)
-            .map_err(|e| {
-                let kind = e.kind();
-                std::io::Error::new(
-                    kind,
-                    format!(
-                        "{}: {}",
-                        filename.as_ref().to_string_lossy(),
-                        e,
-                    ))
-            })?;
+    let grid = puzzle_data.grid;
+    let mut curator = curate::Curator::new(puzzle_data.words);
+    // Read single key presses if possible, otherwise fall back to
+    // reading lines so that the commands can be piped in
+    let raw_terminal = raw_terminal::RawTerminal::new().ok();
+    let mut stdin = std::io::stdin().lock();
+    let mut line = String::new();
+
+    while let Some((word, word_type)) = curator.current() {
+        let counts = build::count_visits(&grid, curator.normal_words());
+
+        println!();
+        print_grid(&grid, &counts);
+        println!(
+            "\n{}/{}: {} ({})",
+            curator.position() + 1,
+            curator.n_words(),
+            word,
+            word_type.name(),
+        );
+        print!(
+            "[n]ormal, [b]onus, e[x]cluded, [p]revious, [q]uit \
+             or Enter to keep: "
+        );
+        std::io::stdout().flush().map_err(|e| e.to_string())?;
+
+        let command = match raw_terminal {
+            Some(ref terminal) => {
+                let key = match terminal.read_key(&mut stdin)? {
+                    Some(raw_terminal::INTERRUPT) => {
+                        return Err("interrupted".to_string());
+                    },
+                    None | Some(raw_terminal::END_OF_FILE) => {
+                        println!();
+                        break;
+                    },
+                    Some(key) => char::from(key),
+                };
+
+                // The terminal doesn’t echo the key
+                if key.is_ascii_graphic() {
+                    println!("{}", key);
+                } else {
+                    println!();
+                }
+
+                curate::parse_command(&key.to_string())
+            },
+            None => {
+                line.clear();
+
+                if stdin.read_line(&mut line).map_err(|e| e.to_string())? == 0
+                {
+                    println!();
+                    break;
+                }
+
+                curate::parse_command(&line)
+            },
+        };
+
+        match command {
+            Some(command) => curator.apply(command),
+            None => println!("Unknown command"),
+        }
     }
 
-    Ok(words)
+    drop(raw_terminal);
+
+    let mut removed_bonus_words = HashSet::new();
+    let mut added_bonus_words = Vec::new();
+    let mut removed_excluded_words = HashSet::new();
+    let mut added_excluded_words = Vec::new();
+
+    for (word, _, word_type) in curator.changes() {
+        match word_type {
+            WordType::Bonus => {
+                if !bonus_words.contains(word) {
+                    added_bonus_words.push(word);
+                }
+            },
+            WordType::Excluded => {
+                if !excluded_words.contains(word) {
+                    added_excluded_words.push(word);
+                }
+            },
+            WordType::Normal | WordType::Featured => {
+                if is_rare(word) {
+                    eprintln!(
+                        "warning: {} will still be a bonus word because \
+                         of --bonus-below",
+                        word,
+                    );
+                }
+            },
+        }
+
+        if word_type != WordType::Bonus && bonus_words.contains(word) {
+            removed_bonus_words.insert(word);
+        }
+
+        if word_type != WordType::Excluded && excluded_words.contains(word) {
+            removed_excluded_words.insert(word);
+        }
+    }
+
+    write_word_lists(
+        &cli.bonus_words,
+        &removed_bonus_words,
+        &added_bonus_words,
+    )?;
+    write_word_lists(
+        &cli.excluded_words,
+        &removed_excluded_words,
+        &added_excluded_words,
+    )?;
+
+    let counts = build::count_visits(&grid, curator.normal_words());
+
+    for (x, y) in build::unused_tiles(&grid, &counts) {
+        eprintln!(
+            "warning: tile “{}” on row {}, column {} isn’t used by any \
+             normal words",
+            grid.at(x, y),
+            y + 1,
+            x + 1,
+        );
+    }
+
+    let mut words = curator.into_words();
+
+    // The user might have changed the type of the word that uses all
+    // of the tiles
+    feature_all_tile_words(&grid, &mut words);
+
+    println!("\n{}", PuzzleData { grid, words });
+
+    Ok(())
+}
+
+fn write_svg(
+    cli: &Cli,
+    grid: &grid::Grid,
+    counts: &counts::GridCounts,
+) -> Result<(), String> {
+    let mut steps = Vec::new();
+
+    let start = match cli.svg_word {
+        Some(ref word) => {
+            match word_finder::Finder::new().find(grid, word, &mut steps) {
+                Some(start) => Some(start),
+                None => {
+                    return Err(format!(
+                        "“{}” can’t be found in the grid",
+                        word,
+                    ));
+                },
+            }
+        },
+        None => None,
+    };
+
+    let route = start.map(|start| svg::Route { start, steps: &steps });
+
+    let image = svg::grid_to_svg(
+        grid,
+        cli.svg_counts.then_some(counts),
+        route.as_ref(),
+    );
+
+    let filename = cli.svg.as_ref().unwrap();
+
+    fs::write(filename, image)
+        .map_err(|e| format!("{}: {}", filename.to_string_lossy(), e))
 }
 
 fn main() -> ExitCode {
     let cli = Cli::parse();
 
-    let dictionary = match fs::read(&cli.dictionary) {
+    let dictionary = match load::load_dictionaries(
+        &cli.dictionary,
+        &cli.add_words,
+        &cli.remove_words,
+    ) {
         Ok(d) => d,
         Err(e) => {
-            eprintln!("{}: {}", cli.dictionary.to_string_lossy(), e);
+            eprintln!("{}", e);
             return ExitCode::FAILURE;
         },
     };
 
-    let bonus_words = match read_word_list(cli.bonus_words.iter()) {
+    let bonus_words = match load::read_word_list(cli.bonus_words.iter()) {
         Ok(d) => d,
         Err(e) => {
             eprintln!("{}", e);
@@ -211,7 +615,7 @@ fn main() -> ExitCode {
         }
     };
 
-    let excluded_words = match read_word_list(cli.excluded_words.iter()) {
+    let excluded_words = match load::read_word_list(cli.excluded_words.iter()) {
         Ok(d) => d,
         Err(e) => {
             eprintln!("{}", e);
@@ -219,7 +623,18 @@ fn main() -> ExitCode {
         }
     };
 
-    let dictionary = dictionary::Dictionary::new(dictionary.into_boxed_slice());
+    // The word lists can only be updated for one puzzle at a time
+    if cli.interactive && cli.puzzles.len() > 1 {
+        eprintln!("--interactive only works with one puzzle");
+        return ExitCode::FAILURE;
+    }
+
+    if cli.svg.is_some() && cli.puzzles.len() > 1 {
+        eprintln!("--svg only works with one puzzle");
+        return ExitCode::FAILURE;
+    }
+
+    let mut grids = Vec::with_capacity(cli.puzzles.len());
 
     for filename in cli.puzzles.iter() {
         let grid_string = match std::fs::read_to_string(filename) {
@@ -230,45 +645,150 @@ fn main() -> ExitCode {
             },
         };
 
-        let grid = match grid::Grid::new(&grid_string) {
-            Ok(g) => g,
+        match grid::Grid::new(&grid_string) {
+            Ok(g) => grids.push(g),
             Err(e) => {
-                eprintln!("{}: {}", filename.to_string_lossy(), e);
+                eprintln!(
+                    "{}",
+                    e.report(&filename.to_string_lossy(), &grid_string),
+                );
                 return ExitCode::FAILURE;
             },
-        };
+        }
+    }
+
+    let n_threads = cli.jobs.unwrap_or_else(|| {
+        std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN)
+    });
+
+    let grid_words = build::search_grids(
+        &grids.iter().collect::<Vec<_>>(),
+        &dictionary,
+        cli.minimum_length,
+        n_threads,
+    );
+
+    let is_rare = |word: &str| {
+        cli.bonus_below.is_some_and(|threshold| {
+            dictionary.value(word).is_some_and(|value| value < threshold)
+        })
+    };
 
-        let words = build::search_words(&grid, &dictionary, cli.minimum_length);
+    let word_type = |word: &str| {
+        if excluded_words.contains(word) {
+            WordType::Excluded
+        } else if bonus_words.contains(word) || is_rare(word) {
+            WordType::Bonus
+        } else {
+            WordType::Normal
+        }
+    };
+
+    let mut result = ExitCode::SUCCESS;
+
+    for ((grid, words), filename) in grids.into_iter()
+        .zip(grid_words)
+        .zip(cli.puzzles.iter())
+    {
         let mut words = words.into_iter()
             .map(|word| {
-                let word_type = if excluded_words.contains(&word) {
-                    WordType::Excluded
-                } else if bonus_words.contains(&word) {
-                    WordType::Bonus
-                } else {
-                    WordType::Normal
-                };
+                let word_type = word_type(&word);
                 (word, word_type)
             })
             .collect::<Vec<(String, WordType)>>();
 
         words.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
 
+        feature_all_tile_words(&grid, &mut words);
+
         let puzzle_data = PuzzleData { grid, words };
 
-        if cli.human_readable {
-            let counts = build::count_visits(
-                &puzzle_data.grid,
-                puzzle_data.words.iter().filter_map(|&(ref word, word_type)| {
-                    (word_type == WordType::Normal).then_some(word)
+        if cli.interactive {
+            if let Err(e) = curate_puzzle(
+                &cli,
+                puzzle_data,
+                &bonus_words,
+                &excluded_words,
+                is_rare,
+            ) {
+                eprintln!("{}", e);
+                result = ExitCode::FAILURE;
+            }
+            continue;
+        }
+
+        let counts = build::count_visits(
+            &puzzle_data.grid,
+            puzzle_data.words.iter().filter_map(|&(ref word, word_type)| {
+                word_type.is_normal().then_some(word)
+            })
+        );
+
+        if cli.svg.is_some() {
+            if let Err(e) = write_svg(&cli, &puzzle_data.grid, &counts) {
+                eprintln!("{}", e);
+                result = ExitCode::FAILURE;
+            }
+        }
+
+        if cli.criticality {
+            let normal_words = puzzle_data.words.iter()
+                .filter_map(|&(ref word, word_type)| {
+                    word_type.is_normal().then_some(word.as_str())
                 })
+                .collect::<Vec<_>>();
+
+            print_criticality(
+                &puzzle_data.grid,
+                &dictionary,
+                cli.minimum_length,
+                n_threads,
+                &normal_words,
+            );
+            continue;
+        }
+
+        if cli.suggest {
+            print_suggestions(
+                &puzzle_data.grid,
+                &dictionary,
+                cli.minimum_length,
+                n_threads,
+                |word| word_type(word) == WordType::Normal,
+            );
+            continue;
+        }
+
+        let unused_tiles = build::unused_tiles(&puzzle_data.grid, &counts);
+
+        for &(x, y) in unused_tiles.iter() {
+            eprintln!(
+                "{}: tile “{}” on row {}, column {} isn’t used by any \
+                 normal words",
+                filename.to_string_lossy(),
+                puzzle_data.grid.at(x, y),
+                y + 1,
+                x + 1,
             );
+        }
+
+        // Still show the human-readable version so that the counts
+        // can help fix the grid
+        let failed = !unused_tiles.is_empty() && !cli.allow_unused_tiles;
 
+        if failed {
+            result = ExitCode::FAILURE;
+        }
+
+        if cli.human_readable {
             print_human_readable(puzzle_data, &counts);
-        } else {
+        } else if cli.format == Format::Json {
+            // The stats in the JSON show the unused tiles
+            println!("{}", puzzle_json::to_json(&puzzle_data, &counts));
+        } else if !failed {
             println!("{}", puzzle_data);
         }
     }
 
-    ExitCode::SUCCESS
+    result
 }
@@ -0,0 +1,209 @@
+// Wordroute – A word game
+// Copyright (C) 2024  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Checks that a line of puzzles.txt is a puzzle that the game can
+// play
+
+use super::puzzle_data::{self, PuzzleData, WordType};
+use super::puzzle::MIN_WORD_LENGTH;
+use super::word_finder;
+use super::build;
+use std::fmt;
+use std::collections::HashSet;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Problem {
+    Parse(puzzle_data::Error),
+    NoRoute(String),
+    // The save states refer to the words by their position in the
+    // sorted list
+    Unsorted(String),
+    Duplicate(String),
+    TooShort(String),
+    NotAllTiles(String),
+    UnusedTile(u32, u32),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::Parse(puzzle_data::Error::Grid(e)) => {
+                match e.position() {
+                    Some(position) => {
+                        write!(f, "{} at column {}", e, position.column)
+                    },
+                    None => e.fmt(f),
+                }
+            },
+            Problem::Parse(e) => e.fmt(f),
+            Problem::NoRoute(word) => {
+                write!(f, "“{}” can’t be found in the grid", word)
+            },
+            Problem::Unsorted(word) => {
+                write!(f, "“{}” isn’t in alphabetical order", word)
+            },
+            Problem::Duplicate(word) => {
+                write!(f, "“{}” is listed more than once", word)
+            },
+            Problem::TooShort(word) => {
+                write!(
+                    f,
+                    "normal word “{}” is shorter than {} letters",
+                    word,
+                    MIN_WORD_LENGTH,
+                )
+            },
+            Problem::NotAllTiles(word) => {
+                write!(f, "featured word “{}” doesn’t use every tile", word)
+            },
+            Problem::UnusedTile(x, y) => {
+                write!(
+                    f,
+                    "tile on row {}, column {} isn’t used by any normal words",
+                    y + 1,
+                    x + 1,
+                )
+            },
+        }
+    }
+}
+
+pub fn check_puzzle(line: &str) -> Vec<Problem> {
+    let puzzle_data = match line.parse::<PuzzleData>() {
+        Ok(p) => p,
+        Err(e) => return vec![Problem::Parse(e)],
+    };
+
+    let mut problems = Vec::new();
+    let mut finder = word_finder::Finder::new();
+    let mut route = Vec::new();
+    let mut normal_words = Vec::new();
+
+    let mut seen_words = HashSet::new();
+    let mut previous: Option<&str> = None;
+
+    for (word, word_type) in puzzle_data.words.iter() {
+        if !seen_words.insert(word.as_str()) {
+            problems.push(Problem::Duplicate(word.clone()));
+        } else if previous.is_some_and(|previous| previous > word.as_str()) {
+            problems.push(Problem::Unsorted(word.clone()));
+        }
+
+        previous = Some(word);
+
+        route.clear();
+
+        let found = finder.find(&puzzle_data.grid, word, &mut route).is_some();
+
+        if !found {
+            problems.push(Problem::NoRoute(word.clone()));
+        }
+
+        if *word_type == WordType::Featured &&
+            found &&
+            !build::uses_all_tiles(&puzzle_data.grid, word)
+        {
+            problems.push(Problem::NotAllTiles(word.clone()));
+        }
+
+        if word_type.is_normal() {
+            if word.chars().count() < MIN_WORD_LENGTH {
+                problems.push(Problem::TooShort(word.clone()));
+            }
+
+            // Words without a route can’t be counted
+            if found {
+                normal_words.push(word);
+            }
+        }
+    }
+
+    let counts = build::count_visits(&puzzle_data.grid, normal_words);
+
+    for (x, y) in build::unused_tiles(&puzzle_data.grid, &counts) {
+        problems.push(Problem::UnusedTile(x, y));
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn good_puzzle() {
+        assert!(check_puzzle("𐑕𐑑𐑨𐑓,𐑓𐑨𐑑𐑕,𐑕𐑑𐑨𐑓,𐑨𐑑:b").is_empty());
+        assert!(check_puzzle("𐑕𐑑𐑨𐑓,𐑓𐑨𐑑𐑕,𐑕𐑑𐑨𐑓:x").is_empty());
+        assert!(check_puzzle("𐑕𐑑𐑨𐑓,𐑓𐑨𐑑𐑕:f").is_empty());
+    }
+
+    #[test]
+    fn parse_error() {
+        let problems = check_puzzle("𐑕𐑑x,𐑕𐑑");
+        assert_eq!(problems.len(), 1);
+        assert_eq!(
+            &problems[0].to_string(),
+            "invalid letter “x” at column 3",
+        );
+
+        assert_eq!(
+            &check_puzzle("")
+                .iter()
+                .map(Problem::to_string)
+                .collect::<Vec<_>>(),
+            &["empty grid"],
+        );
+        assert_eq!(
+            check_puzzle("𐑕𐑑𐑨𐑓,𐑕𐑑𐑨𐑓:q"),
+            [Problem::Parse(puzzle_data::Error::InvalidWordType)],
+        );
+    }
+
+    #[test]
+    fn problems() {
+        assert_eq!(
+            check_puzzle("𐑕𐑑𐑨𐑓:𐑿...,𐑕𐑑𐑨𐑓,𐑓𐑨𐑑𐑕,𐑕𐑑𐑨𐑓,𐑕𐑑𐑨𐑓𐑿,𐑨𐑑"),
+            [
+                Problem::Unsorted("𐑓𐑨𐑑𐑕".to_string()),
+                Problem::Duplicate("𐑕𐑑𐑨𐑓".to_string()),
+                Problem::NoRoute("𐑕𐑑𐑨𐑓𐑿".to_string()),
+                Problem::TooShort("𐑨𐑑".to_string()),
+                Problem::UnusedTile(0, 1),
+            ],
+        );
+
+        assert_eq!(
+            &Problem::UnusedTile(2, 0).to_string(),
+            "tile on row 1, column 3 isn’t used by any normal words",
+        );
+        assert_eq!(
+            check_puzzle("𐑕𐑑𐑨𐑓,𐑕𐑑𐑨:f,𐑕𐑑𐑨𐑓"),
+            [
+                Problem::NotAllTiles("𐑕𐑑𐑨".to_string()),
+                Problem::TooShort("𐑕𐑑𐑨".to_string()),
+            ],
+        );
+        assert_eq!(
+            &Problem::NotAllTiles("𐑕𐑑𐑨".to_string()).to_string(),
+            "featured word “𐑕𐑑𐑨” doesn’t use every tile",
+        );
+        assert_eq!(
+            &Problem::TooShort("𐑨𐑑".to_string()).to_string(),
+            "normal word “𐑨𐑑” is shorter than 4 letters",
+        );
+    }
+}
@@ -0,0 +1,72 @@
+// Wordroute – A word game
+// Copyright (C) 2024  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+#[allow(dead_code)]
+mod grid;
+#[allow(dead_code)]
+mod build;
+#[allow(dead_code)]
+mod dictionary;
+#[allow(dead_code)]
+mod layered_dictionary;
+mod directions;
+#[allow(dead_code)]
+mod word_finder;
+mod counts;
+#[allow(dead_code)]
+mod puzzle_data;
+#[allow(dead_code)]
+mod puzzle;
+#[allow(dead_code)]
+mod save_state;
+#[allow(dead_code)]
+mod shavicode;
+mod check;
+
+use std::process::ExitCode;
+use std::ffi::OsString;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(name = "CheckPuzzles")]
+struct Cli {
+    #[arg(value_name = "PUZZLES", default_value = "puzzles.txt")]
+    puzzles: OsString,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let filename = cli.puzzles.to_string_lossy();
+
+    let data = match std::fs::read_to_string(&cli.puzzles) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("{}: {}", filename, e);
+            return ExitCode::FAILURE;
+        },
+    };
+
+    let mut result = ExitCode::SUCCESS;
+
+    for (line_num, line) in data.lines().enumerate() {
+        for problem in check::check_puzzle(line) {
+            eprintln!("{}:{}: {}", filename, line_num + 1, problem);
+            result = ExitCode::FAILURE;
+        }
+    }
+
+    result
+}
@@ -0,0 +1,280 @@
+// Wordroute – A word game
+// Copyright (C) 2024  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Steps through the words of a puzzle so that the type of each one
+// can be changed interactively, and works out how the word lists need
+// to change afterwards.
+
+use super::puzzle_data::WordType;
+use std::collections::HashSet;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Command {
+    Keep,
+    Set(WordType),
+    Previous,
+    Quit,
+}
+
+pub struct Curator {
+    words: Vec<(String, WordType)>,
+    original_types: Vec<WordType>,
+    position: usize,
+}
+
+pub fn parse_command(line: &str) -> Option<Command> {
+    match line.trim() {
+        "" => Some(Command::Keep),
+        "n" => Some(Command::Set(WordType::Normal)),
+        "b" => Some(Command::Set(WordType::Bonus)),
+        "x" => Some(Command::Set(WordType::Excluded)),
+        "p" => Some(Command::Previous),
+        "q" => Some(Command::Quit),
+        _ => None,
+    }
+}
+
+impl Curator {
+    pub fn new(words: Vec<(String, WordType)>) -> Curator {
+        let original_types = words.iter().map(|&(_, t)| t).collect();
+
+        Curator {
+            words,
+            original_types,
+            position: 0,
+        }
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn n_words(&self) -> usize {
+        self.words.len()
+    }
+
+    // Returns the word that is waiting for a command, or None once
+    // all of the words have been seen
+    pub fn current(&self) -> Option<(&str, WordType)> {
+        self.words.get(self.position)
+            .map(|(word, word_type)| (word.as_str(), *word_type))
+    }
+
+    pub fn normal_words(&self) -> impl Iterator<Item = &str> {
+        self.words.iter().filter_map(|(word, word_type)| {
+            word_type.is_normal().then_some(word.as_str())
+        })
+    }
+
+    pub fn apply(&mut self, command: Command) {
+        match command {
+            Command::Keep => self.position += 1,
+            Command::Set(word_type) => {
+                if let Some((_, t)) = self.words.get_mut(self.position) {
+                    *t = word_type;
+                    self.position += 1;
+                }
+            },
+            Command::Previous => {
+                self.position = self.position.saturating_sub(1);
+            },
+            Command::Quit => self.position = self.words.len(),
+        }
+    }
+
+    // Iterates the words whose type has been changed along with the
+    // type that they had before
+    pub fn changes(&self) -> impl Iterator<Item = (&str, WordType, WordType)> {
+        self.words.iter()
+            .zip(self.original_types.iter())
+            .filter_map(|((word, new_type), &old_type)| {
+                (*new_type != old_type)
+                    .then_some((word.as_str(), old_type, *new_type))
+            })
+    }
+
+    pub fn into_words(self) -> Vec<(String, WordType)> {
+        self.words
+    }
+}
+
+// Removes the lines that contain one of the removed words and adds
+// the added words to the end. Everything else, including comments, is
+// kept as it was.
+pub fn update_word_list(
+    contents: &str,
+    removed: &HashSet<&str>,
+    added: &[&str],
+) -> String {
+    let mut result = String::new();
+
+    for line in contents.lines() {
+        if !removed.contains(line.trim()) {
+            result.push_str(line);
+            result.push('\n');
+        }
+    }
+
+    for word in added {
+        result.push_str(word);
+        result.push('\n');
+    }
+
+    result
+}
+
+// Works out the new contents of a set of word lists. The removed
+// words are taken out of whichever lists contain them and the added
+// words go at the end of the last list. The result is None for each
+// list that doesn’t change so that its file isn’t rewritten.
+pub fn update_word_lists<T: AsRef<str>>(
+    lists: &[T],
+    removed: &HashSet<&str>,
+    added: &[&str],
+) -> Vec<Option<String>> {
+    lists.iter().enumerate().map(|(i, contents)| {
+        let contents = contents.as_ref();
+        let added = if i + 1 == lists.len() { added } else { &[] };
+
+        (!added.is_empty() ||
+         contents.lines().any(|line| removed.contains(line.trim())))
+            .then(|| update_word_list(contents, removed, added))
+    }).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_curator() -> Curator {
+        Curator::new(vec![
+            ("𐑒𐑨𐑚".to_string(), WordType::Normal),
+            ("𐑚𐑨𐑑".to_string(), WordType::Bonus),
+            ("𐑕𐑑𐑨𐑓𐑑".to_string(), WordType::Normal),
+        ])
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(parse_command(""), Some(Command::Keep));
+        assert_eq!(parse_command(" \n"), Some(Command::Keep));
+        assert_eq!(parse_command("n"), Some(Command::Set(WordType::Normal)));
+        assert_eq!(parse_command("b\n"), Some(Command::Set(WordType::Bonus)));
+        assert_eq!(
+            parse_command(" x "),
+            Some(Command::Set(WordType::Excluded)),
+        );
+        assert_eq!(parse_command("p"), Some(Command::Previous));
+        assert_eq!(parse_command("q"), Some(Command::Quit));
+        assert_eq!(parse_command("z"), None);
+        assert_eq!(parse_command("nb"), None);
+    }
+
+    #[test]
+    fn step_through() {
+        let mut curator = make_curator();
+
+        assert_eq!(curator.n_words(), 3);
+        assert_eq!(curator.current(), Some(("𐑒𐑨𐑚", WordType::Normal)));
+
+        curator.apply(Command::Previous);
+        assert_eq!(curator.position(), 0);
+
+        curator.apply(Command::Set(WordType::Excluded));
+        assert_eq!(curator.current(), Some(("𐑚𐑨𐑑", WordType::Bonus)));
+
+        curator.apply(Command::Keep);
+        assert_eq!(curator.normal_words().collect::<Vec<_>>(), ["𐑕𐑑𐑨𐑓𐑑"]);
+
+        curator.apply(Command::Previous);
+        curator.apply(Command::Set(WordType::Normal));
+        curator.apply(Command::Keep);
+        assert!(curator.current().is_none());
+
+        // Changes past the end are ignored
+        curator.apply(Command::Set(WordType::Bonus));
+        assert!(curator.current().is_none());
+
+        assert_eq!(
+            curator.changes().collect::<Vec<_>>(),
+            [
+                ("𐑒𐑨𐑚", WordType::Normal, WordType::Excluded),
+                ("𐑚𐑨𐑑", WordType::Bonus, WordType::Normal),
+            ],
+        );
+
+        let words = curator.into_words();
+        assert_eq!(words[0].1, WordType::Excluded);
+        assert_eq!(words[1].1, WordType::Normal);
+        assert_eq!(words[2].1, WordType::Normal);
+    }
+
+    #[test]
+    fn quit() {
+        let mut curator = make_curator();
+
+        curator.apply(Command::Set(WordType::Bonus));
+        curator.apply(Command::Quit);
+
+        assert!(curator.current().is_none());
+        assert_eq!(curator.changes().count(), 1);
+    }
+
+    #[test]
+    fn word_list() {
+        assert_eq!(
+            &update_word_list(
+                "# Bonus words\n𐑒𐑨𐑚\n  𐑚𐑨𐑑  \n𐑕𐑑𐑨𐑓𐑑",
+                &["𐑚𐑨𐑑"].into(),
+                &["𐑑𐑨𐑚"],
+            ),
+            "# Bonus words\n𐑒𐑨𐑚\n𐑕𐑑𐑨𐑓𐑑\n𐑑𐑨𐑚\n",
+        );
+        assert_eq!(
+            &update_word_list("", &HashSet::new(), &["𐑑𐑨𐑚"]),
+            "𐑑𐑨𐑚\n",
+        );
+    }
+
+    #[test]
+    fn word_lists() {
+        let lists = ["𐑒𐑨𐑚\n𐑚𐑨𐑑\n", "𐑕𐑑𐑨𐑓𐑑\n", "# New words\n"];
+
+        assert_eq!(
+            update_word_lists(&lists, &["𐑚𐑨𐑑"].into(), &[]),
+            [Some("𐑒𐑨𐑚\n".to_string()), None, None],
+        );
+        assert_eq!(
+            update_word_lists(&lists, &HashSet::new(), &["𐑑𐑨𐑚"]),
+            [None, None, Some("# New words\n𐑑𐑨𐑚\n".to_string())],
+        );
+        assert_eq!(
+            update_word_lists(&lists, &["𐑕𐑑𐑨𐑓𐑑"].into(), &["𐑑𐑨𐑚"]),
+            [
+                None,
+                Some("".to_string()),
+                Some("# New words\n𐑑𐑨𐑚\n".to_string()),
+            ],
+        );
+        assert_eq!(
+            update_word_lists(&lists, &HashSet::new(), &[]),
+            [None, None, None],
+        );
+        assert!(
+            update_word_lists::<&str>(&[], &HashSet::new(), &[]).is_empty()
+        );
+    }
+}
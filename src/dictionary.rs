@@ -14,30 +14,53 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
 pub struct Dictionary {
     data: Box<[u8]>,
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    TruncatedNode(usize),
+    OffsetOutOfRange(usize),
+    InvalidLetter(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::TruncatedNode(pos) => {
+                write!(f, "truncated node at byte {}", pos)
+            },
+            Error::OffsetOutOfRange(pos) => {
+                write!(f, "offset out of range in node at byte {}", pos)
+            },
+            Error::InvalidLetter(pos) => {
+                write!(f, "invalid UTF-8 letter in node at byte {}", pos)
+            },
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Walker<'a> {
     data: &'a [u8],
 }
 
 impl<'a> Walker<'a> {
-    pub fn new(dictionary: &Dictionary) -> Option<Walker> {
+    pub fn new(dictionary: &Dictionary) -> Option<Walker<'_>> {
         // Skip the root node
-        let Some(Node { remainder, child_offset, .. }) =
-            Node::extract(&dictionary.data)
-        else {
-            return None;
-        };
+        let Node { remainder, child_offset, .. } =
+            Node::extract(&dictionary.data)?;
 
         if child_offset == 0 {
             return None;
         }
 
         Some(Walker {
-            data: &remainder[child_offset..]
+            data: remainder.get(child_offset..)?,
         })
     }
 
@@ -69,10 +92,7 @@ impl<'a> Walker<'a> {
         let mut data = self.data;
 
         loop {
-            let Some(node) = Node::extract(data)
-            else {
-                return None;
-            };
+            let node = Node::extract(data)?;
 
             if node.letter == next {
                 return if node.child_offset == 0 {
@@ -87,13 +107,40 @@ impl<'a> Walker<'a> {
                     return None;
                 }
 
-                data = match node.remainder.get(node.sibling_offset..) {
-                    Some(d) => d,
-                    None => return None,
-                };
+                data = node.remainder.get(node.sibling_offset..)?;
             }
         }
     }
+
+    // Get the value that was stored with the word that ends at this
+    // node, if there is one. The end of a word is marked with a node
+    // for the letter '\0' which never has any children, so its child
+    // offset is used to store the value plus one instead. Dictionaries
+    // without values always have zero there.
+    pub fn value(&self) -> Option<u32> {
+        Siblings::new(self.data)
+            .find(|node| node.letter == '\0')
+            .and_then(|node| node.child_offset.checked_sub(1))
+            .and_then(|value| u32::try_from(value).ok())
+    }
+
+    // Iterate over the letters that can follow this node along with
+    // a walker for each of them
+    pub fn children(&self) -> Children<'a> {
+        Children {
+            siblings: Siblings::new(self.data),
+        }
+    }
+
+    // Iterate over all of the words that start from this node. Each
+    // word is prefixed with the given string, which would normally
+    // be the letters that were used to get to this node.
+    pub fn words(&self, prefix: &str) -> Words<'a> {
+        Words {
+            word: prefix.to_string(),
+            stack: vec![Siblings::new(self.data)],
+        }
+    }
 }
 
 impl Dictionary {
@@ -102,6 +149,218 @@ impl Dictionary {
             data
         }
     }
+
+    // Check that the whole trie can be walked before accepting the
+    // data. All of the offsets are unsigned and relative to a point
+    // after the start of the node, so they can only ever point
+    // forwards. That means the structure can’t contain any cycles as
+    // long as every offset is in range.
+    pub fn new_checked(data: Box<[u8]>) -> Result<Dictionary, Error> {
+        if data.is_empty() {
+            return Err(Error::TruncatedNode(0));
+        }
+
+        let mut visited = vec![false; data.len()];
+        let mut stack = vec![0];
+
+        while let Some(pos) = stack.pop() {
+            if std::mem::replace(&mut visited[pos], true) {
+                continue;
+            }
+
+            let node = check_node(&data, pos)?;
+            let letter_pos = data.len() - node.remainder.len();
+
+            // The child offset of an end marker is the word’s value
+            let child_offset = if node.letter == '\0' {
+                0
+            } else {
+                node.child_offset
+            };
+
+            for offset in [node.sibling_offset, child_offset] {
+                if offset == 0 {
+                    continue;
+                }
+
+                match letter_pos.checked_add(offset) {
+                    Some(target) if target < data.len() => stack.push(target),
+                    _ => return Err(Error::OffsetOutOfRange(pos)),
+                }
+            }
+        }
+
+        Ok(Dictionary { data })
+    }
+
+    pub fn from_words<I, T>(words: I) -> Dictionary
+        where I: IntoIterator<Item = T>,
+              T: AsRef<str>
+    {
+        let mut builder = Builder::new();
+
+        for word in words {
+            builder.add_word(word.as_ref());
+        }
+
+        Dictionary::new(builder.build())
+    }
+
+    pub fn words(&self) -> Words<'_> {
+        self.words_with_prefix("")
+    }
+
+    pub fn value(&self, word: &str) -> Option<u32> {
+        Walker::new(self)
+            .and_then(|walker| {
+                word.chars().try_fold(walker, |walker, ch| walker.step(ch))
+            })
+            .and_then(|walker| walker.value())
+    }
+
+    pub fn words_with_prefix(&self, prefix: &str) -> Words<'_> {
+        let walker = Walker::new(self).and_then(|walker| {
+            prefix.chars().try_fold(walker, |walker, ch| walker.step(ch))
+        });
+
+        match walker {
+            Some(walker) => walker.words(prefix),
+            None => Words {
+                word: String::new(),
+                stack: Vec::new(),
+            },
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Siblings<'a> {
+    data: Option<&'a [u8]>,
+}
+
+impl<'a> Siblings<'a> {
+    fn new(data: &'a [u8]) -> Siblings<'a> {
+        Siblings {
+            data: Some(data),
+        }
+    }
+}
+
+impl<'a> Iterator for Siblings<'a> {
+    type Item = Node<'a>;
+
+    fn next(&mut self) -> Option<Node<'a>> {
+        let node = Node::extract(self.data.take()?)?;
+
+        if node.sibling_offset != 0 {
+            self.data = node.remainder.get(node.sibling_offset..);
+        }
+
+        Some(node)
+    }
+}
+
+#[derive(Clone)]
+pub struct Children<'a> {
+    siblings: Siblings<'a>,
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = (char, Walker<'a>);
+
+    fn next(&mut self) -> Option<(char, Walker<'a>)> {
+        loop {
+            let node = self.siblings.next()?;
+
+            if node.letter == '\0' || node.child_offset == 0 {
+                continue;
+            }
+
+            if let Some(data) = node.remainder.get(node.child_offset..) {
+                return Some((node.letter, Walker { data }));
+            }
+        }
+    }
+}
+
+pub struct Words<'a> {
+    word: String,
+    // One entry for each letter in the word after the prefix plus one
+    // for the node that the iterator started from
+    stack: Vec<Siblings<'a>>,
+}
+
+impl<'a> Iterator for Words<'a> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        loop {
+            let siblings = self.stack.last_mut()?;
+
+            let Some(node) = siblings.next()
+            else {
+                self.stack.pop();
+
+                if !self.stack.is_empty() {
+                    self.word.pop();
+                }
+
+                continue;
+            };
+
+            if node.letter == '\0' {
+                return Some(self.word.clone());
+            }
+
+            if node.child_offset == 0 {
+                continue;
+            }
+
+            if let Some(data) = node.remainder.get(node.child_offset..) {
+                self.word.push(node.letter);
+                self.stack.push(Siblings::new(data));
+            }
+        }
+    }
+}
+
+fn check_offset(data: &[u8], pos: usize) -> Result<&[u8], Error> {
+    read_offset(data).map(|(rest, _)| rest).ok_or_else(|| {
+        // read_offset fails either if the data ends before the last
+        // byte of the number or if the number doesn’t fit in a usize
+        if data.iter().all(|&byte| byte & 0x80 != 0) &&
+            data.len() * 7 <= usize::BITS as usize
+        {
+            Error::TruncatedNode(pos)
+        } else {
+            Error::OffsetOutOfRange(pos)
+        }
+    })
+}
+
+fn check_node(data: &[u8], pos: usize) -> Result<Node<'_>, Error> {
+    let node_data = &data[pos..];
+
+    let rest = check_offset(node_data, pos)?;
+    let rest = check_offset(rest, pos)?;
+
+    let Some(&first_byte) = rest.first()
+    else {
+        return Err(Error::TruncatedNode(pos));
+    };
+
+    let utf8_len = std::cmp::max(first_byte.leading_ones() as usize, 1);
+
+    let Some(letter) = rest.get(0..utf8_len)
+    else {
+        return Err(Error::TruncatedNode(pos));
+    };
+
+    if std::str::from_utf8(letter).is_err() {
+        return Err(Error::InvalidLetter(pos));
+    }
+
+    Ok(Node::extract(node_data).unwrap())
 }
 
 fn read_offset(data: &[u8]) -> Option<(&[u8], usize)> {
@@ -146,6 +405,159 @@ impl<'a> Node<'a> {
     }
 }
 
+fn write_offset_reversed(offset: usize, buf: &mut Vec<u8>) {
+    let mut bytes = [0u8; usize::BITS.div_ceil(7) as usize];
+    let mut n_bytes = 0;
+    let mut offset = offset;
+
+    loop {
+        bytes[n_bytes] = (offset & 0x7f) as u8;
+        offset >>= 7;
+        n_bytes += 1;
+
+        if offset == 0 {
+            break;
+        }
+
+        bytes[n_bytes - 1] |= 0x80;
+    }
+
+    buf.extend(bytes[0..n_bytes].iter().rev());
+}
+
+#[derive(Default)]
+struct BuilderNode {
+    // The end of a word is marked with a child node for the letter
+    // '\0'. Using a BTreeMap keeps the children sorted so the end
+    // marker is always first.
+    children: BTreeMap<char, BuilderNode>,
+    // Only used for the end marker
+    value: Option<u32>,
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct BuilderEntry {
+    letter: char,
+    child_pos: Option<EndPos>,
+    value: Option<u32>,
+}
+
+// Position of a node measured from the end of the data. The data is
+// written backwards so that the target of every offset is already
+// known when a node is written.
+type EndPos = usize;
+
+pub struct Builder {
+    root: BuilderNode,
+}
+
+impl Builder {
+    pub fn new() -> Builder {
+        Builder {
+            root: BuilderNode::default(),
+        }
+    }
+
+    pub fn add_word(&mut self, word: &str) {
+        self.add_word_internal(word, None);
+    }
+
+    pub fn add_word_with_value(&mut self, word: &str, value: u32) {
+        self.add_word_internal(word, Some(value));
+    }
+
+    fn add_word_internal(&mut self, word: &str, value: Option<u32>) {
+        let mut node = &mut self.root;
+
+        for letter in word.chars() {
+            node = node.children.entry(letter).or_default();
+        }
+
+        node.children.entry('\0').or_default().value = value;
+    }
+
+    pub fn build(&self) -> Box<[u8]> {
+        let mut buf = Vec::new();
+        let mut lists = HashMap::new();
+
+        let child_pos = write_children(&self.root, &mut buf, &mut lists);
+
+        write_node_reversed(
+            &BuilderEntry { letter: '*', child_pos, value: None },
+            None,
+            &mut buf,
+        );
+
+        buf.reverse();
+
+        buf.into_boxed_slice()
+    }
+}
+
+fn write_node_reversed(
+    entry: &BuilderEntry,
+    sibling_pos: Option<EndPos>,
+    buf: &mut Vec<u8>,
+) -> EndPos {
+    let mut utf8 = [0u8; 4];
+    let utf8 = entry.letter.encode_utf8(&mut utf8).as_bytes();
+
+    // The offsets are relative to the start of the letter
+    let letter_pos = buf.len() + utf8.len();
+
+    let child_offset = match (entry.child_pos, entry.value) {
+        (Some(pos), _) => letter_pos - pos,
+        // End markers store the value in the child offset instead
+        (None, Some(value)) => value as usize + 1,
+        (None, None) => 0,
+    };
+
+    buf.extend(utf8.iter().rev());
+    write_offset_reversed(child_offset, buf);
+    write_offset_reversed(
+        sibling_pos.map(|pos| letter_pos - pos).unwrap_or(0),
+        buf,
+    );
+
+    buf.len()
+}
+
+fn write_children(
+    node: &BuilderNode,
+    buf: &mut Vec<u8>,
+    lists: &mut HashMap<Vec<BuilderEntry>, EndPos>,
+) -> Option<EndPos> {
+    if node.children.is_empty() {
+        return None;
+    }
+
+    let key = node.children.iter().map(|(&letter, child)| {
+        BuilderEntry {
+            letter,
+            child_pos: write_children(child, buf, lists),
+            value: child.value,
+        }
+    }).collect::<Vec<_>>();
+
+    // Share identical lists of siblings so that common suffixes are
+    // only stored once
+    if let Some(&pos) = lists.get(&key) {
+        return Some(pos);
+    }
+
+    let mut sibling_pos = None;
+
+    for entry in key.iter().rev() {
+        sibling_pos = Some(write_node_reversed(entry, sibling_pos, buf));
+    }
+
+    let pos = sibling_pos.unwrap();
+
+    lists.insert(key, pos);
+
+    Some(pos)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -156,7 +568,7 @@ mod test {
         assert_eq!(node.sibling_offset, 7);
         assert_eq!(node.child_offset, 8);
         assert_eq!(node.letter, 'c');
-        assert_eq!(node.remainder, &[b'c']);
+        assert_eq!(node.remainder, b"c");
 
         let node = Node::extract(&[7, 8, 0xc4, 0x89]).unwrap();
         assert_eq!(node.sibling_offset, 7);
@@ -176,7 +588,7 @@ mod test {
         assert_eq!(node.sibling_offset, 0b11111111111111);
         assert_eq!(node.child_offset, 0b10000000000000);
         assert_eq!(node.letter, 'c');
-        assert_eq!(node.remainder, &[b'c']);
+        assert_eq!(node.remainder, b"c");
     }
 
     fn make_test_dictionary() -> Dictionary {
@@ -189,7 +601,7 @@ mod test {
             0x04, b'e', 0x00, 0x01, b'o', 0x00, 0x00, 0x00,
         ];
 
-        Dictionary::new(Box::new(DICTIONARY_BYTES.clone()))
+        Dictionary::new(Box::new(DICTIONARY_BYTES))
     }
 
     #[test]
@@ -211,4 +623,202 @@ mod test {
         assert!(w.is_end());
         assert!(w.step('a').is_none());
     }
+
+    #[test]
+    fn words() {
+        let dictionary = make_test_dictionary();
+
+        assert_eq!(
+            &dictionary.words().collect::<Vec<_>>(),
+            &["a", "app", "apple", "b", "c", "ĉapelo"],
+        );
+
+        assert_eq!(
+            &dictionary.words_with_prefix("ap").collect::<Vec<_>>(),
+            &["app", "apple"],
+        );
+        assert_eq!(
+            &dictionary.words_with_prefix("apple").collect::<Vec<_>>(),
+            &["apple"],
+        );
+        assert!(dictionary.words_with_prefix("apples").next().is_none());
+        assert!(dictionary.words_with_prefix("z").next().is_none());
+
+        let dictionary = Dictionary::new(Box::new([]));
+        assert!(dictionary.words().next().is_none());
+    }
+
+    #[test]
+    fn children() {
+        let dictionary = make_test_dictionary();
+
+        let w = Walker::new(&dictionary).unwrap();
+        assert_eq!(
+            &w.children().map(|(ch, _)| ch).collect::<String>(),
+            "abcĉ",
+        );
+
+        let (_, w) = w.children().next().unwrap();
+        let (letter, w) = w.children().next().unwrap();
+        assert_eq!(letter, 'p');
+        assert_eq!(&w.words("ap").collect::<Vec<_>>(), &["app", "apple"]);
+
+        let w = w.step('p').unwrap().step('l').unwrap().step('e').unwrap();
+        assert!(w.children().next().is_none());
+    }
+
+    #[test]
+    fn new_checked() {
+        let dictionary = make_test_dictionary();
+
+        assert!(Dictionary::new_checked(dictionary.data.clone()).is_ok());
+        assert!(Dictionary::new_checked(Builder::new().build()).is_ok());
+
+        assert_eq!(
+            Dictionary::new_checked(Box::new([])).err(),
+            Some(Error::TruncatedNode(0)),
+        );
+
+        // Cut off the end of the shared end-of-word node
+        let data = dictionary.data[0..dictionary.data.len() - 1].to_vec();
+        assert_eq!(
+            Dictionary::new_checked(data.into_boxed_slice()).err(),
+            Some(Error::TruncatedNode(49)),
+        );
+
+        // Make the child offset of “b” point past the end
+        let mut data = dictionary.data.clone();
+        data[7] = 0x7f;
+        assert_eq!(
+            Dictionary::new_checked(data).err(),
+            Some(Error::OffsetOutOfRange(6)),
+        );
+
+        // Offset that doesn’t fit in a usize
+        let mut data = vec![0x00, 0x01, b'*'];
+        data.extend_from_slice(&[0xff; 12]);
+        data.extend_from_slice(&[0x00, b'a']);
+        assert_eq!(
+            Dictionary::new_checked(data.into_boxed_slice()).err(),
+            Some(Error::OffsetOutOfRange(3)),
+        );
+
+        // Break the UTF-8 sequence for “ĉ”
+        let mut data = dictionary.data.clone();
+        data[18] = b'x';
+        assert_eq!(
+            Dictionary::new_checked(data).err(),
+            Some(Error::InvalidLetter(15)),
+        );
+
+        assert_eq!(
+            &Error::InvalidLetter(15).to_string(),
+            "invalid UTF-8 letter in node at byte 15",
+        );
+    }
+
+    #[test]
+    fn offset_round_trip() {
+        for offset in [0, 1, 0x7f, 0x80, 0x3fff, 0x4000, u32::MAX as usize] {
+            let mut buf = Vec::new();
+            write_offset_reversed(offset, &mut buf);
+            buf.reverse();
+            assert_eq!(read_offset(&buf), Some((&[][..], offset)));
+        }
+    }
+
+    #[test]
+    fn builder() {
+        let mut builder = Builder::new();
+
+        for word in ["ĉapelo", "apple", "a", "app", "b", "c"] {
+            builder.add_word(word);
+        }
+
+        let dictionary = Dictionary::new(builder.build());
+
+        let w = Walker::new(&dictionary).unwrap();
+        assert!(!w.is_end());
+        assert!(w.step('b').unwrap().is_end());
+        assert!(w.step('c').unwrap().is_end());
+        let w = w.step('a').unwrap();
+        assert!(w.is_end());
+        assert!(w.step('q').is_none());
+        let w = w.step('p').unwrap();
+        assert!(!w.is_end());
+        let w = w.step('p').unwrap();
+        assert!(w.is_end());
+        let w = w.step('l').unwrap();
+        assert!(!w.is_end());
+        let w = w.step('e').unwrap();
+        assert!(w.is_end());
+        assert!(w.step('a').is_none());
+
+        let w = Walker::new(&dictionary).unwrap();
+        let w = "ĉapelo".chars().fold(w, |w, ch| w.step(ch).unwrap());
+        assert!(w.is_end());
+
+        assert_eq!(
+            &dictionary.words().collect::<Vec<_>>(),
+            &["a", "app", "apple", "b", "c", "ĉapelo"],
+        );
+    }
+
+    #[test]
+    fn builder_shares_suffixes() {
+        let mut builder = Builder::new();
+
+        builder.add_word("pasi");
+        builder.add_word("kasi");
+
+        // Root node, the two first letters and then a single shared
+        // copy of “asi” followed by the end marker
+        assert_eq!(builder.build().len(), 3 * 7);
+    }
+
+    #[test]
+    fn values() {
+        let mut builder = Builder::new();
+
+        builder.add_word_with_value("pasi", 12);
+        builder.add_word_with_value("kasi", 0);
+        builder.add_word_with_value("kas", 1000);
+        builder.add_word("pas");
+
+        let data = builder.build();
+        let dictionary = Dictionary::new_checked(data).unwrap();
+
+        assert_eq!(dictionary.value("pasi"), Some(12));
+        assert_eq!(dictionary.value("kasi"), Some(0));
+        assert_eq!(dictionary.value("kas"), Some(1000));
+        assert_eq!(dictionary.value("pas"), None);
+        assert_eq!(dictionary.value("pa"), None);
+        assert_eq!(dictionary.value("zzz"), None);
+
+        let w = Walker::new(&dictionary).unwrap().step('k').unwrap();
+        assert_eq!(w.value(), None);
+        let w = w.step('a').unwrap().step('s').unwrap();
+        assert!(w.is_end());
+        assert_eq!(w.value(), Some(1000));
+        assert!(w.step('i').unwrap().is_end());
+
+        // The values mustn’t interfere with the other iterators
+        assert_eq!(
+            &dictionary.words().collect::<Vec<_>>(),
+            &["kas", "kasi", "pas", "pasi"],
+        );
+        assert_eq!(
+            &w.children().map(|(ch, _)| ch).collect::<String>(),
+            "i",
+        );
+
+        // Dictionaries without values don’t have any
+        assert_eq!(make_test_dictionary().value("apple"), None);
+    }
+
+    #[test]
+    fn empty_builder() {
+        let dictionary = Dictionary::new(Builder::new().build());
+        assert!(Walker::new(&dictionary).is_none());
+    }
 }
@@ -14,53 +14,121 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-// The grid is arranged in hexagons as if every other row is shifted
-// right by half a position. That means that for example on even rows
-// (where the first “zeroth” row is even) the downward directions are
-// the coordinate directly below and the coordinate down and left,
-// whereas on odd rows they are the coordinate directly below and the
-// coordinate down and right.
+// A topology describes which tiles are adjacent to each other. Each
+// neighbour of a tile is reached by taking one of the numbered
+// directions. The directions are ordered so that the opposite of
+// direction d is always n_directions() - 1 - d.
+
+pub trait Topology {
+    fn n_directions(&self) -> u8;
+
+    fn step(&self, x: u32, y: u32, direction: u8) -> (u32, u32);
+
+    #[cfg(any(target_arch = "wasm32", test))]
+    // Given a position and the direction that was used to get there,
+    // return the starting position.
+    fn reverse(&self, x: u32, y: u32, direction: u8) -> (u32, u32) {
+        self.step(x, y, self.n_directions() - 1 - direction)
+    }
+}
+
+// The hex grid is arranged in hexagons as if every other row is
+// shifted right by half a position. That means that for example on
+// even rows (where the first “zeroth” row is even) the downward
+// directions are the coordinate directly below and the coordinate
+// down and left, whereas on odd rows they are the coordinate directly
+// below and the coordinate down and right.
 
 // a b c d
 //  e f g h
 // i j k l
 
-pub const N_DIRECTIONS: u8 = 6;
+pub struct HexTopology;
+
+impl Topology for HexTopology {
+    fn n_directions(&self) -> u8 {
+        6
+    }
+
+    fn step(&self, x: u32, y: u32, direction: u8) -> (u32, u32) {
+        let y_off;
 
-pub fn step(x: u32, y: u32, direction: u8) -> (u32, u32) {
-    let y_off;
+        if direction < 2 {
+            y_off = -1;
+        } else if direction < 4 {
+            let x_off = if direction & 1 == 0 {
+                -1
+            } else {
+                1
+            };
 
-    if direction < 2 {
-        y_off = -1;
-    } else if direction < 4 {
-        let x_off = if direction & 1 == 0 {
-            -1
+            return (x.wrapping_add_signed(x_off), y);
         } else {
-            1
-        };
+            assert!(direction < 6);
+            y_off = 1;
+        }
+
+        let x_off = (direction & 1) as i32 - 1 + (y & 1) as i32;
+
+        (x.wrapping_add_signed(x_off), y.wrapping_add_signed(y_off))
+    }
+}
+
+// The square grid has eight neighbours for each tile including the
+// diagonals. The directions are numbered in reading order:
 
-        return (x.wrapping_add_signed(x_off), y);
-    } else {
-        assert!(direction < N_DIRECTIONS);
-        y_off = 1;
+// 0 1 2
+// 3 . 4
+// 5 6 7
+
+pub struct SquareTopology;
+
+impl Topology for SquareTopology {
+    fn n_directions(&self) -> u8 {
+        8
     }
 
-    let x_off = (direction & 1) as i32 - 1 + (y & 1) as i32;
+    fn step(&self, x: u32, y: u32, direction: u8) -> (u32, u32) {
+        let (x_off, y_off) = match direction {
+            0 => (-1, -1),
+            1 => (0, -1),
+            2 => (1, -1),
+            3 => (-1, 0),
+            4 => (1, 0),
+            5 => (-1, 1),
+            6 => (0, 1),
+            7 => (1, 1),
+            _ => unreachable!("invalid direction {}", direction),
+        };
+
+        (x.wrapping_add_signed(x_off), y.wrapping_add_signed(y_off))
+    }
+}
 
-    (x.wrapping_add_signed(x_off), y.wrapping_add_signed(y_off))
+// The shape of the tiles in a grid, which determines its topology.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    Hexagon,
+    Square,
 }
 
-#[cfg(any(target_arch = "wasm32", test))]
-// Given a position and the direction that was used to get there,
-// return the starting position.
-pub fn reverse(x: u32, y: u32, direction: u8) -> (u32, u32) {
-    step(x, y, 5 - direction)
+impl Shape {
+    pub fn topology(self) -> &'static dyn Topology {
+        match self {
+            Shape::Hexagon => &HexTopology,
+            Shape::Square => &SquareTopology,
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    fn step(x: u32, y: u32, direction: u8) -> (u32, u32) {
+        HexTopology.step(x, y, direction)
+    }
+
     #[test]
     fn step_all_directions() {
         // Even rows
@@ -89,11 +157,35 @@ mod test {
         assert_eq!(step(0, 0, 1), (0, u32::MAX));
     }
 
+    #[test]
+    fn square_all_directions() {
+        let topology = SquareTopology;
+
+        assert_eq!(topology.step(1, 1, 0), (0, 0));
+        assert_eq!(topology.step(1, 1, 1), (1, 0));
+        assert_eq!(topology.step(1, 1, 2), (2, 0));
+        assert_eq!(topology.step(1, 1, 3), (0, 1));
+        assert_eq!(topology.step(1, 1, 4), (2, 1));
+        assert_eq!(topology.step(1, 1, 5), (0, 2));
+        assert_eq!(topology.step(1, 1, 6), (1, 2));
+        assert_eq!(topology.step(1, 1, 7), (2, 2));
+        // The same on odd and even rows
+        assert_eq!(topology.step(1, 2, 7), (2, 3));
+
+        assert_eq!(topology.step(0, 0, 0), (u32::MAX, u32::MAX));
+    }
+
     #[test]
     fn reverse_matches_step() {
-        for dir in 0..N_DIRECTIONS {
-            let next = step(2, 1, dir);
-            assert_eq!(reverse(next.0, next.1, dir), (2, 1));
+        for shape in [Shape::Hexagon, Shape::Square] {
+            let topology = shape.topology();
+
+            for y in 1..=2 {
+                for dir in 0..topology.n_directions() {
+                    let next = topology.step(2, y, dir);
+                    assert_eq!(topology.reverse(next.0, next.1, dir), (2, y));
+                }
+            }
         }
     }
 }
@@ -0,0 +1,70 @@
+// Wordroute – A word game
+// Copyright (C) 2024  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+#[allow(dead_code)]
+mod dictionary;
+
+use std::process::ExitCode;
+use std::io::{BufWriter, Write};
+use std::ffi::OsString;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(name = "DumpDictionary")]
+struct Cli {
+    #[arg(value_name = "DICTIONARY")]
+    dictionary: OsString,
+    #[arg(value_name = "PREFIX", default_value = "")]
+    prefix: String,
+}
+
+fn dump_words(
+    dictionary: &dictionary::Dictionary,
+    prefix: &str,
+) -> Result<(), std::io::Error> {
+    let mut out = BufWriter::new(std::io::stdout().lock());
+
+    for word in dictionary.words_with_prefix(prefix) {
+        writeln!(out, "{}", word)?;
+    }
+
+    out.flush()
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let dictionary = match std::fs::read(&cli.dictionary)
+        .map_err(|e| e.to_string())
+        .and_then(|d| {
+            dictionary::Dictionary::new_checked(d.into_boxed_slice())
+                .map_err(|e| e.to_string())
+        })
+    {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("{}: {}", cli.dictionary.to_string_lossy(), e);
+            return ExitCode::FAILURE;
+        },
+    };
+
+    if let Err(e) = dump_words(&dictionary, &cli.prefix) {
+        eprintln!("{}", e);
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
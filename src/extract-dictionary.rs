@@ -34,6 +34,8 @@ struct Cli {
     readlex: OsString,
     #[arg(short, long, value_name = "LENGTH", default_value_t = 4)]
     minimum_length: usize,
+    #[arg(short, long)]
+    frequencies: bool,
 }
 
 #[derive(Deserialize)]
@@ -42,20 +44,22 @@ struct Entry {
     shavian: String,
     pos: String,
     var: String,
+    #[serde(default)]
+    freq: u32,
 }
 
-static BANNED_POSITIONS: [&'static str; 1] = [
+static BANNED_POSITIONS: [&str; 1] = [
     "NP0",
 ];
 
-static ALLOWED_VARIATIONS: [&'static str; 1] = [
+static ALLOWED_VARIATIONS: [&str; 1] = [
     "RRP",
 ];
 
 type ReadLexMap = HashMap<String, Vec<Entry>>;
 
 fn is_shavian(s: &str) -> bool {
-    s.chars().all(|ch| ch >= '𐑐' && ch <= '𐑿')
+    s.chars().all(|ch| ('𐑐'..='𐑿').contains(&ch))
 }
 
 fn write_dictionaries<D, B>(
@@ -63,11 +67,12 @@ fn write_dictionaries<D, B>(
     mut bonus_words: B,
     map: ReadLexMap,
     minimum_length: usize,
+    frequencies: bool,
 ) -> Result<(), std::io::Error>
     where D: Write,
           B: Write
 {
-    let mut all_words = HashSet::new();
+    let mut all_words = HashMap::<String, u32>::new();
     let mut allowed_words = HashSet::new();
 
     for (_, entries) in map.into_iter() {
@@ -85,19 +90,26 @@ fn write_dictionaries<D, B>(
                 allowed_words.insert(entry.shavian.clone());
             }
 
-            all_words.insert(entry.shavian);
+            // The same spelling can appear in multiple entries, for
+            // example for different parts of speech
+            let freq = all_words.entry(entry.shavian).or_insert(0);
+            *freq = freq.saturating_add(entry.freq);
         }
     }
 
     let mut all_words = all_words.into_iter().collect::<Vec<_>>();
     all_words.sort_unstable();
 
-    for word in all_words.into_iter() {
+    for (word, freq) in all_words.into_iter() {
         if !allowed_words.contains(&word) {
             writeln!(&mut bonus_words, "{}", &word)?;
         }
 
-        writeln!(&mut dictionary, "{}", word)?;
+        if frequencies {
+            writeln!(&mut dictionary, "{} {}", word, freq)?;
+        } else {
+            writeln!(&mut dictionary, "{}", word)?;
+        }
     }
 
     Ok(())
@@ -145,6 +157,7 @@ fn main() -> ExitCode {
         bonus_words,
         map,
         cli.minimum_length,
+        cli.frequencies,
     ) {
         eprintln!("{}", e);
         return ExitCode::FAILURE;
@@ -0,0 +1,333 @@
+// Wordroute – A word game
+// Copyright (C) 2024  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Fills in the tiles of a template grid with random letters and then
+// keeps changing one tile at a time for as long as that doesn’t move
+// the grid further away from the targets.
+
+use super::grid::Grid;
+use super::layered_dictionary::LayeredDictionary;
+use super::build::{self, Stats};
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::ops::RangeInclusive;
+
+// Number of random changes to try in each round. This doesn’t depend
+// on the number of threads so that the same seed makes the same grids
+// on any machine.
+const CHANGES_PER_ROUND: usize = 8;
+
+pub struct Targets {
+    pub words: RangeInclusive<usize>,
+    pub longest_word: usize,
+}
+
+pub struct Candidate {
+    pub grid: Grid,
+    pub stats: Stats,
+}
+
+// Small xorshift generator so that the same seed always makes the
+// same grids
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        // The state must never be zero
+        Rng(seed | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+pub struct Generator<'a> {
+    template: &'a Grid,
+    dictionary: &'a LayeredDictionary,
+    minimum_length: usize,
+    targets: Targets,
+    n_threads: NonZeroUsize,
+    // Positions of the tiles that need a letter
+    tiles: Vec<(u32, u32)>,
+    // Letters to choose from along with the running total of their
+    // weights
+    letters: Vec<(char, u64)>,
+    rng: Rng,
+}
+
+impl Targets {
+    // How far the grid is from meeting the targets. Unused tiles are
+    // the most important, then the longest word and then the number
+    // of words. All of the parts are zero if the targets are met.
+    fn distance(&self, stats: &Stats) -> (usize, usize, usize) {
+        let n_words = if stats.n_words < *self.words.start() {
+            self.words.start() - stats.n_words
+        } else {
+            stats.n_words.saturating_sub(*self.words.end())
+        };
+
+        (
+            stats.unused_tiles,
+            self.longest_word.saturating_sub(stats.longest_word),
+            n_words,
+        )
+    }
+
+    pub fn are_met_by(&self, stats: &Stats) -> bool {
+        self.distance(stats) == (0, 0, 0)
+    }
+}
+
+// Counts how often each letter appears in the words so that the
+// generator can pick common letters more often
+pub fn letter_frequencies<I, T>(words: I) -> HashMap<char, u64>
+    where I: IntoIterator<Item = T>,
+          T: AsRef<str>
+{
+    let mut frequencies = HashMap::new();
+
+    for word in words {
+        for ch in word.as_ref().chars() {
+            *frequencies.entry(ch).or_insert(0) += 1;
+        }
+    }
+
+    frequencies
+}
+
+impl<'a> Generator<'a> {
+    pub fn new(
+        template: &'a Grid,
+        dictionary: &'a LayeredDictionary,
+        minimum_length: usize,
+        targets: Targets,
+        letter_frequencies: &HashMap<char, u64>,
+        seed: u64,
+        n_threads: NonZeroUsize,
+    ) -> Generator<'a> {
+        let tiles = (0..template.height()).flat_map(|y| {
+            (0..template.width()).map(move |x| (x, y))
+        }).filter(|&(x, y)| template.at(x, y) != ".").collect();
+
+        let mut letters = letter_frequencies.iter()
+            .filter(|&(_, &frequency)| frequency > 0)
+            .map(|(&letter, &frequency)| (letter, frequency))
+            .collect::<Vec<_>>();
+
+        // Sort so that the seed always picks the same letters
+        letters.sort_unstable();
+
+        let mut total = 0;
+
+        for (_, weight) in letters.iter_mut() {
+            total += *weight;
+            *weight = total;
+        }
+
+        Generator {
+            template,
+            dictionary,
+            minimum_length,
+            targets,
+            n_threads,
+            tiles,
+            letters,
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn targets(&self) -> &Targets {
+        &self.targets
+    }
+
+    fn random_letter(&mut self) -> String {
+        let total = self.letters.last().map(|&(_, total)| total).unwrap_or(0);
+
+        if total == 0 {
+            return ".".to_string();
+        }
+
+        let choice = self.rng.next() % total;
+        let index = self.letters.partition_point(|&(_, sum)| sum <= choice);
+
+        self.letters[index].0.to_string()
+    }
+
+    fn evaluate(&self, grids: Vec<Grid>) -> Vec<Candidate> {
+        let words = build::search_grids(
+            &grids.iter().collect::<Vec<_>>(),
+            self.dictionary,
+            self.minimum_length,
+            self.n_threads,
+        );
+
+        grids.into_iter().zip(words).map(|(grid, words)| {
+            let stats = Stats::new(&grid, words);
+            Candidate { grid, stats }
+        }).collect()
+    }
+
+    // Fills the template with random letters and then tries up to
+    // max_steps rounds of changes to get it to meet the targets. Each
+    // round tries a few changes at once and keeps the best one. The
+    // result might not meet the targets if it ran out of steps.
+    pub fn generate(&mut self, max_steps: usize) -> Candidate {
+        let mut grid = self.template.clone();
+
+        for i in 0..self.tiles.len() {
+            let (x, y) = self.tiles[i];
+            let letter = self.random_letter();
+            grid = grid.with_tile(x, y, &letter);
+        }
+
+        let mut best = self.evaluate(vec![grid]).pop().unwrap();
+
+        if self.tiles.is_empty() {
+            return best;
+        }
+
+        for _ in 0..max_steps {
+            if self.targets.are_met_by(&best.stats) {
+                break;
+            }
+
+            let changes = (0..CHANGES_PER_ROUND).map(|_| {
+                let (x, y) = self.tiles[self.rng.below(self.tiles.len())];
+                let letter = self.random_letter();
+                best.grid.with_tile(x, y, &letter)
+            }).collect::<Vec<_>>();
+
+            let candidate = self.evaluate(changes)
+                .into_iter()
+                .min_by_key(|candidate| self.targets.distance(&candidate.stats))
+                .unwrap();
+
+            // Also accept changes that are just as good so that the
+            // search can wander out of flat areas
+            if self.targets.distance(&candidate.stats) <=
+                self.targets.distance(&best.stats)
+            {
+                best = candidate;
+            }
+        }
+
+        best
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::dictionary::Dictionary;
+
+    fn make_dictionary() -> LayeredDictionary {
+        Dictionary::from_words(["𐑒𐑨𐑚", "𐑕𐑑𐑨𐑓𐑑", "𐑚𐑨𐑑"]).into()
+    }
+
+    #[test]
+    fn distance() {
+        let stats = Stats { n_words: 2, longest_word: 3, unused_tiles: 1 };
+
+        let targets = Targets { words: 1..=2, longest_word: 3 };
+        assert_eq!(targets.distance(&stats), (1, 0, 0));
+        assert!(!targets.are_met_by(&stats));
+
+        let stats = Stats { unused_tiles: 0, ..stats };
+        assert!(targets.are_met_by(&stats));
+
+        assert_eq!(
+            Targets { words: 3..=4, longest_word: 5 }.distance(&stats),
+            (0, 2, 1),
+        );
+        assert_eq!(
+            Targets { words: 0..=1, longest_word: 0 }.distance(&stats),
+            (0, 0, 1),
+        );
+    }
+
+    #[test]
+    fn frequencies() {
+        let frequencies = letter_frequencies(["𐑒𐑨𐑚", "𐑚𐑨𐑑"]);
+
+        assert_eq!(frequencies.len(), 4);
+        assert_eq!(frequencies[&'𐑨'], 2);
+        assert_eq!(frequencies[&'𐑑'], 1);
+    }
+
+    #[test]
+    fn generate() {
+        let template = Grid::new("#𐑿 𐑿 𐑿 : . . .").unwrap();
+        let dictionary = make_dictionary();
+        let frequencies = letter_frequencies(["𐑒𐑨𐑚", "𐑚𐑨𐑑"]);
+        let targets = Targets { words: 1..=2, longest_word: 3 };
+
+        let mut generator = Generator::new(
+            &template,
+            &dictionary,
+            3,
+            targets,
+            &frequencies,
+            42,
+            NonZeroUsize::new(2).unwrap(),
+        );
+
+        let candidate = generator.generate(1000);
+
+        assert!(generator.targets().are_met_by(&candidate.stats));
+        assert!(candidate.stats.n_words >= 1);
+        assert_eq!(candidate.grid.at(0, 1), ".");
+        assert_eq!(candidate.grid.shape(), template.shape());
+
+        let word = (0..3).map(|x| candidate.grid.at(x, 0)).collect::<String>();
+        assert!(word == "𐑒𐑨𐑚" || word == "𐑚𐑨𐑑" ||
+                word == "𐑚𐑨𐑒" || word == "𐑑𐑨𐑚");
+    }
+
+    #[test]
+    fn same_seed() {
+        let template = Grid::new("𐑿𐑿𐑿𐑿\n𐑿𐑿𐑿𐑿").unwrap();
+        let dictionary = make_dictionary();
+        let frequencies = letter_frequencies(["𐑒𐑨𐑚", "𐑕𐑑𐑨𐑓𐑑", "𐑚𐑨𐑑"]);
+
+        let grids = [1, 3].map(|n_threads| {
+            let mut generator = Generator::new(
+                &template,
+                &dictionary,
+                3,
+                Targets { words: 4..=4, longest_word: 5 },
+                &frequencies,
+                7,
+                NonZeroUsize::new(n_threads).unwrap(),
+            );
+
+            let grid = generator.generate(50).grid;
+
+            (0..grid.height()).flat_map(|y| {
+                (0..grid.width()).map(move |x| (x, y))
+            }).map(|(x, y)| grid.at(x, y).to_string()).collect::<Vec<_>>()
+        });
+
+        assert_eq!(grids[0], grids[1]);
+    }
+}
@@ -0,0 +1,216 @@
+// Wordroute – A word game
+// Copyright (C) 2024  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+mod grid;
+#[allow(dead_code)]
+mod build;
+#[allow(dead_code)]
+mod dictionary;
+#[allow(dead_code)]
+mod layered_dictionary;
+mod directions;
+#[allow(dead_code)]
+mod word_finder;
+mod counts;
+#[allow(dead_code)]
+mod shavicode;
+mod generate;
+#[allow(dead_code)]
+mod load;
+
+use std::{fs, process::ExitCode, ffi::OsString};
+use std::num::NonZeroUsize;
+use std::time::{SystemTime, UNIX_EPOCH};
+use clap::Parser;
+use layered_dictionary::LayeredDictionary;
+use generate::{Generator, Targets};
+
+#[derive(Parser)]
+#[command(name = "GeneratePuzzle")]
+struct Cli {
+    /// Puzzle file whose tiles give the shape of the grid. The
+    /// letters on the tiles are ignored.
+    #[arg(value_name = "TEMPLATE")]
+    template: OsString,
+    #[arg(short, long, value_name = "FILE", required = true)]
+    dictionary: Vec<OsString>,
+    #[arg(short, long, value_name = "LENGTH", default_value_t = 4)]
+    minimum_length: usize,
+    /// Minimum number of words in the puzzle
+    #[arg(long, value_name = "COUNT", default_value_t = 40)]
+    min_words: usize,
+    /// Maximum number of words in the puzzle
+    #[arg(long, value_name = "COUNT", default_value_t = 100)]
+    max_words: usize,
+    /// Minimum length of the longest word in the puzzle
+    #[arg(long, value_name = "LENGTH", default_value_t = 7)]
+    longest_word: usize,
+    /// Number of candidate grids to print
+    #[arg(short = 'n', long, value_name = "COUNT", default_value_t = 5)]
+    candidates: usize,
+    /// Number of times to start again with a new random grid before
+    /// giving up
+    #[arg(long, value_name = "COUNT", default_value_t = 100)]
+    attempts: usize,
+    /// Maximum number of rounds of tile changes for each attempt
+    #[arg(long, value_name = "COUNT", default_value_t = 1000)]
+    steps: usize,
+    /// Seed for the random number generator. Defaults to the time
+    #[arg(long, value_name = "SEED")]
+    seed: Option<u64>,
+    /// Number of threads to use to search for words. Defaults to
+    /// the number of available CPUs
+    #[arg(short, long, value_name = "N")]
+    jobs: Option<NonZeroUsize>,
+}
+
+fn load_template(filename: &OsString) -> Result<grid::Grid, String> {
+    let source = fs::read_to_string(filename)
+        .map_err(|e| format!("{}: {}", filename.to_string_lossy(), e))?;
+
+    grid::Grid::new(&source)
+        .map_err(|e| e.report(&filename.to_string_lossy(), &source))
+}
+
+// Prints the grid in the same format as the puzzle files so that it
+// can be copied into one
+fn print_candidate(candidate: &generate::Candidate) {
+    let grid = &candidate.grid;
+
+    for y in 0..grid.height() {
+        let mut line = String::new();
+
+        if y == 0 && grid.shape() == directions::Shape::Square {
+            line.push('#');
+        }
+
+        // Hexagonal grids have every odd row shifted right
+        if grid.shape() == directions::Shape::Hexagon && y & 1 != 0 {
+            line.push(' ');
+        }
+
+        for x in 0..grid.width() {
+            if x > 0 {
+                line.push(' ');
+            }
+
+            line.push_str(grid.at(x, y));
+        }
+
+        println!("{}", line.trim_end());
+    }
+
+    println!(
+        "\n{} words, longest word {} letters",
+        candidate.stats.n_words,
+        candidate.stats.longest_word,
+    );
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    if cli.min_words > cli.max_words {
+        eprintln!(
+            "--min-words ({}) is greater than --max-words ({})",
+            cli.min_words,
+            cli.max_words,
+        );
+        return ExitCode::FAILURE;
+    }
+
+    let template = match load_template(&cli.template) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        },
+    };
+
+    let layers = match cli.dictionary.iter()
+        .map(load::load_dictionary)
+        .collect::<Result<Vec<_>, String>>()
+    {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        },
+    };
+
+    let frequencies = generate::letter_frequencies(
+        layers.iter().flat_map(|layer| layer.words())
+    );
+
+    let dictionary = LayeredDictionary::new(layers, std::iter::empty::<&str>());
+
+    let seed = cli.seed.unwrap_or_else(|| {
+        SystemTime::now().duration_since(UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64)
+            .unwrap_or(0)
+    });
+
+    // Print the seed so that a good run can be repeated
+    eprintln!("Seed: {}", seed);
+
+    let n_threads = cli.jobs.unwrap_or_else(|| {
+        std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN)
+    });
+
+    let mut generator = Generator::new(
+        &template,
+        &dictionary,
+        cli.minimum_length,
+        Targets {
+            words: cli.min_words..=cli.max_words,
+            longest_word: cli.longest_word,
+        },
+        &frequencies,
+        seed,
+        n_threads,
+    );
+
+    let mut n_found = 0;
+
+    for _ in 0..cli.attempts {
+        if n_found >= cli.candidates {
+            break;
+        }
+
+        let candidate = generator.generate(cli.steps);
+
+        if generator.targets().are_met_by(&candidate.stats) {
+            if n_found > 0 {
+                println!();
+            }
+
+            print_candidate(&candidate);
+
+            n_found += 1;
+        }
+    }
+
+    if n_found < cli.candidates {
+        eprintln!(
+            "Only found {} of {} grids that meet the targets",
+            n_found,
+            cli.candidates,
+        );
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
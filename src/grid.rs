@@ -15,37 +15,143 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::fmt;
+use std::collections::HashMap;
 use super::shavicode;
+use super::directions::{Shape, Topology};
 
-#[derive(Debug)]
+// A grid that starts with this character uses square tiles instead of
+// hexagons.
+const SQUARE_MARKER: char = '#';
+
+// Letters inside these brackets are grouped into a single tile
+const TILE_START: char = '[';
+const TILE_END: char = ']';
+
+#[derive(Debug, Clone)]
 pub struct Grid {
-    values: Box<[char]>,
+    values: Box<[Box<str>]>,
     width: u32,
     height: u32,
+    shape: Shape,
+    // The positions of the tiles starting with each letter, in
+    // reading order
+    letter_positions: HashMap<char, Vec<(u32, u32)>>,
+}
+
+// The game scales the grid to fit the width of the page, which is at
+// most 40em (see wordroute.css). With 32 tiles in a row each tile is
+// already less than 20 pixels across at the default font size and the
+// letters are barely readable, so a longer row is almost certainly a
+// mistake in the grid.
+const MAX_WIDTH: usize = 32;
+
+// Position of a character in the text of a grid. Both numbers count
+// from 1.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Error {
-    EmptyGrid
+    EmptyGrid,
+    InvalidLetter(Position, char),
+    RowTooLong(Position),
+    UnterminatedTile(Position),
+    EmptyTile(Position),
+}
+
+impl Error {
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            Error::EmptyGrid => None,
+            Error::InvalidLetter(position, _) |
+            Error::RowTooLong(position) |
+            Error::UnterminatedTile(position) |
+            Error::EmptyTile(position) => Some(*position),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    // Describes the error like a compiler would, with the position of
+    // the error and the line of the grid that it is in
+    pub fn report(&self, filename: &str, source: &str) -> String {
+        let Some(position) = self.position()
+        else {
+            return format!("{}: {}", filename, self);
+        };
+
+        let mut report = format!("{}:{}: error: {}", filename, position, self);
+
+        if let Some(line) = source.lines().nth(position.line - 1) {
+            let line_number = position.line.to_string();
+            // Keep any tabs so that the marker lines up
+            let indent = line.chars()
+                .take(position.column - 1)
+                .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+                .collect::<String>();
+
+            report.push_str(&format!(
+                "\n{} | {}\n{:width$} | {}^",
+                line_number,
+                line,
+                "",
+                indent,
+                width = line_number.len(),
+            ));
+        }
+
+        report
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Error::EmptyGrid => write!(f, "empty grid"),
+            Error::InvalidLetter(_, ch) => write!(f, "invalid letter “{}”", ch),
+            Error::RowTooLong(_) => {
+                write!(f, "row is longer than {} tiles", MAX_WIDTH)
+            },
+            Error::UnterminatedTile(_) => write!(f, "unterminated tile"),
+            Error::EmptyTile(_) => write!(f, "empty tile"),
         }
     }
 }
 
 impl fmt::Display for Grid {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.shape == Shape::Square {
+            write!(f, "{}", SQUARE_MARKER)?;
+        }
+
         for y in 0..self.height() {
             if y > 0 {
                 write!(f, ":")?;
             }
 
             for x in 0..self.width() {
-                write!(f, "{}", shavicode::encode_char(self.at(x, y)))?;
+                let tile = self.at(x, y);
+                let is_group = tile.chars().nth(1).is_some();
+
+                if is_group {
+                    write!(f, "{}", TILE_START)?;
+                }
+
+                for ch in tile.chars() {
+                    write!(f, "{}", shavicode::encode_char(ch))?;
+                }
+
+                if is_group {
+                    write!(f, "{}", TILE_END)?;
+                }
             }
         }
 
@@ -53,48 +159,161 @@ impl fmt::Display for Grid {
     }
 }
 
-fn lines(s: &str) -> std::str::Split<&[char]> {
-    s.split(&['\n', ':'])
+fn positioned_chars(s: &str) -> impl Iterator<Item = (Position, char)> + '_ {
+    let mut position = Position { line: 1, column: 1 };
+
+    s.chars().map(move |ch| {
+        let result = (position, ch);
+
+        if ch == '\n' {
+            position.line += 1;
+            position.column = 1;
+        } else {
+            position.column += 1;
+        }
+
+        result
+    })
+}
+
+fn is_row_end(ch: char) -> bool {
+    ch == '\n' || ch == ':'
+}
+
+fn decode_letter(position: Position, ch: char) -> Result<char, Error> {
+    let letter = shavicode::decode_char(ch);
+
+    if shavicode::is_letter(letter) {
+        Ok(letter)
+    } else {
+        Err(Error::InvalidLetter(position, ch))
+    }
+}
+
+type Row = Vec<Box<str>>;
+
+fn parse_rows(s: &str) -> Result<(Shape, Vec<Row>), Error> {
+    let mut shape = Shape::Hexagon;
+    let mut rows = vec![Vec::new()];
+    // The square marker is only allowed before the first tile
+    let mut at_start = true;
+
+    let mut chars = positioned_chars(s)
+        .filter(|&(_, ch)| ch == '\n' || !ch.is_whitespace());
+
+    while let Some((position, ch)) = chars.next() {
+        let tile = match ch {
+            '\n' | ':' => {
+                at_start &= ch == '\n';
+                rows.push(Vec::new());
+                continue;
+            },
+            SQUARE_MARKER if at_start => {
+                shape = Shape::Square;
+                rows.truncate(1);
+                at_start = false;
+                continue;
+            },
+            '.' => ".".into(),
+            TILE_START => {
+                let mut tile = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some((_, TILE_END)) => break,
+                        Some((position, ch)) if !is_row_end(ch) => {
+                            tile.push(decode_letter(position, ch)?);
+                        },
+                        _ => return Err(Error::UnterminatedTile(position)),
+                    }
+                }
+
+                if tile.is_empty() {
+                    return Err(Error::EmptyTile(position));
+                }
+
+                tile.into_boxed_str()
+            },
+            _ => decode_letter(position, ch)?.to_string().into_boxed_str(),
+        };
+
+        at_start = false;
+
+        let row = rows.last_mut().unwrap();
+
+        if row.len() >= MAX_WIDTH {
+            return Err(Error::RowTooLong(position));
+        }
+
+        row.push(tile);
+    }
+
+    Ok((shape, rows))
 }
 
 impl Grid {
     pub fn new(s: &str) -> Result<Grid, Error> {
+        let (shape, rows) = parse_rows(s)?;
+
         // Find the longest line
-        let width = lines(s).map(|line| {
-            line.chars().filter(|ch| !ch.is_whitespace()).count()
-        }).max().unwrap_or(0);
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+
+        let mut values = Vec::new();
+
+        for (row, tiles) in rows.into_iter().enumerate() {
+            if !tiles.is_empty() {
+                values.resize(row * width, ".".into());
+                values.extend(tiles);
+            }
+        }
 
-        if width < 1 {
+        if values.iter().all(|tile| &**tile == ".") {
             return Err(Error::EmptyGrid);
         }
 
-        let mut values = Vec::new();
+        let height = values.len().div_ceil(width);
+
+        values.resize(width * height, ".".into());
+
+        Ok(Grid::from_values(
+            values.into_boxed_slice(),
+            width as u32,
+            shape,
+        ))
+    }
+
+    fn from_values(values: Box<[Box<str>]>, width: u32, shape: Shape) -> Grid {
+        let mut letter_positions = HashMap::<_, Vec<_>>::new();
+
+        for (i, tile) in values.iter().enumerate() {
+            if &**tile != "." {
+                let position = (i as u32 % width, i as u32 / width);
 
-        for (row, line) in lines(s).enumerate() {
-            let line = line.trim_end();
-
-            if !line.is_empty() {
-                values.resize(row * width, '.');
-                values.extend(
-                    line.chars()
-                        .filter_map(|ch| {
-                            (!ch.is_whitespace()).then(|| {
-                                shavicode::decode_char(ch)
-                            })
-                        })
-                );
+                letter_positions.entry(tile.chars().next().unwrap())
+                    .or_default()
+                    .push(position);
             }
         }
 
-        let height = (values.len() + width - 1) / width;
+        Grid {
+            height: values.len() as u32 / width,
+            values,
+            width,
+            shape,
+            letter_positions,
+        }
+    }
 
-        values.resize(width * height, '.');
+    #[cfg(not(target_arch = "wasm32"))]
+    // Makes a copy of the grid with the tile at the given position
+    // replaced
+    pub fn with_tile(&self, x: u32, y: u32, tile: &str) -> Grid {
+        assert!(x < self.width);
 
-        Ok(Grid {
-            values: values.into_boxed_slice(),
-            width: width as u32,
-            height: height as u32,
-        })
+        let mut values = self.values.clone();
+        values[(y * self.width + x) as usize] = tile.into();
+
+        Grid::from_values(values, self.width, self.shape)
     }
 
     pub fn width(&self) -> u32 {
@@ -105,10 +324,37 @@ impl Grid {
         self.height
     }
 
-    pub fn at(&self, x: u32, y: u32) -> char {
+    // Returns the positions of all the tiles whose first letter is
+    // the given letter in reading order
+    pub fn positions(&self, letter: char) -> &[(u32, u32)] {
+        self.letter_positions.get(&letter).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    // Iterates the positions of the tiles grouped by their first
+    // letter
+    pub fn letter_positions(
+        &self,
+    ) -> impl Iterator<Item = (char, &[(u32, u32)])> {
+        self.letter_positions.iter()
+            .map(|(&letter, positions)| (letter, positions.as_slice()))
+    }
+
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    pub fn topology(&self) -> &'static dyn Topology {
+        self.shape.topology()
+    }
+
+    // Returns the letters on the tile at the given position. This
+    // is usually a single letter but it can be more. Gaps in the
+    // grid are represented as “.”.
+    pub fn at(&self, x: u32, y: u32) -> &str {
         assert!(x < self.width);
 
-        self.values[(y * self.width + x) as usize]
+        &self.values[(y * self.width + x) as usize]
     }
 }
 
@@ -130,15 +376,15 @@ mod test {
 
         assert_eq!(grid.width(), 1);
         assert_eq!(grid.height(), 2);
-        assert_eq!(grid.at(0, 0), '𐑪');
-        assert_eq!(grid.at(0, 1), '𐑫');
+        assert_eq!(grid.at(0, 0), "𐑪");
+        assert_eq!(grid.at(0, 1), "𐑫");
 
         let grid = Grid::new("a\nb\n     \n     ").unwrap();
 
         assert_eq!(grid.width(), 1);
         assert_eq!(grid.height(), 2);
-        assert_eq!(grid.at(0, 0), '𐑪');
-        assert_eq!(grid.at(0, 1), '𐑫');
+        assert_eq!(grid.at(0, 0), "𐑪");
+        assert_eq!(grid.at(0, 1), "𐑫");
     }
 
     #[test]
@@ -147,12 +393,12 @@ mod test {
 
         assert_eq!(grid.width(), 2);
         assert_eq!(grid.height(), 3);
-        assert_eq!(grid.at(0, 0), '𐑐');
-        assert_eq!(grid.at(1, 0), '𐑑');
-        assert_eq!(grid.at(0, 1), '𐑒');
-        assert_eq!(grid.at(1, 1), '.');
-        assert_eq!(grid.at(0, 2), '𐑓');
-        assert_eq!(grid.at(1, 2), '.');
+        assert_eq!(grid.at(0, 0), "𐑐");
+        assert_eq!(grid.at(1, 0), "𐑑");
+        assert_eq!(grid.at(0, 1), "𐑒");
+        assert_eq!(grid.at(1, 1), ".");
+        assert_eq!(grid.at(0, 2), "𐑓");
+        assert_eq!(grid.at(1, 2), ".");
     }
 
     #[test]
@@ -164,14 +410,14 @@ mod test {
 
         assert_eq!(grid.width(), 4);
         assert_eq!(grid.height(), 2);
-        assert_eq!(grid.at(0, 0), '𐑖');
-        assert_eq!(grid.at(1, 0), '𐑷');
-        assert_eq!(grid.at(2, 0), '𐑦');
-        assert_eq!(grid.at(3, 0), '𐑟');
-        assert_eq!(grid.at(0, 1), '𐑜');
-        assert_eq!(grid.at(1, 1), '𐑮');
-        assert_eq!(grid.at(2, 1), '𐑱');
-        assert_eq!(grid.at(3, 1), '𐑑');
+        assert_eq!(grid.at(0, 0), "𐑖");
+        assert_eq!(grid.at(1, 0), "𐑷");
+        assert_eq!(grid.at(2, 0), "𐑦");
+        assert_eq!(grid.at(3, 0), "𐑟");
+        assert_eq!(grid.at(0, 1), "𐑜");
+        assert_eq!(grid.at(1, 1), "𐑮");
+        assert_eq!(grid.at(2, 1), "𐑱");
+        assert_eq!(grid.at(3, 1), "𐑑");
     }
 
     #[test]
@@ -183,12 +429,12 @@ mod test {
 
         assert_eq!(grid.width(), 3);
         assert_eq!(grid.height(), 2);
-        assert_eq!(grid.at(0, 0), '𐑪');
-        assert_eq!(grid.at(1, 0), '𐑫');
-        assert_eq!(grid.at(2, 0), '𐑬');
-        assert_eq!(grid.at(0, 1), '𐑭');
-        assert_eq!(grid.at(1, 1), '𐑮');
-        assert_eq!(grid.at(2, 1), '𐑯');
+        assert_eq!(grid.at(0, 0), "𐑪");
+        assert_eq!(grid.at(1, 0), "𐑫");
+        assert_eq!(grid.at(2, 0), "𐑬");
+        assert_eq!(grid.at(0, 1), "𐑭");
+        assert_eq!(grid.at(1, 1), "𐑮");
+        assert_eq!(grid.at(2, 1), "𐑯");
     }
 
     #[test]
@@ -199,17 +445,173 @@ mod test {
 
         assert_eq!(grid.width(), 3);
         assert_eq!(grid.height(), 2);
-        assert_eq!(grid.at(0, 0), '𐑪');
-        assert_eq!(grid.at(1, 0), '𐑫');
-        assert_eq!(grid.at(2, 0), '𐑬');
-        assert_eq!(grid.at(0, 1), '𐑭');
-        assert_eq!(grid.at(1, 1), '𐑮');
-        assert_eq!(grid.at(2, 1), '𐑯');
+        assert_eq!(grid.at(0, 0), "𐑪");
+        assert_eq!(grid.at(1, 0), "𐑫");
+        assert_eq!(grid.at(2, 0), "𐑬");
+        assert_eq!(grid.at(0, 1), "𐑭");
+        assert_eq!(grid.at(1, 1), "𐑮");
+        assert_eq!(grid.at(2, 1), "𐑯");
     }
 
     #[test]
     fn format() {
         assert_eq!(&Grid::new("a").unwrap().to_string(), "a");
         assert_eq!(&Grid::new("abc\ndef").unwrap().to_string(), "abc:def");
+        assert_eq!(&Grid::new("#ab:cd").unwrap().to_string(), "#ab:cd");
+    }
+
+    #[test]
+    fn square() {
+        let grid = Grid::new("abc").unwrap();
+        assert_eq!(grid.shape(), Shape::Hexagon);
+        assert_eq!(grid.topology().n_directions(), 6);
+
+        let grid = Grid::new("\n  # a b\n    c d").unwrap();
+        assert_eq!(grid.shape(), Shape::Square);
+        assert_eq!(grid.topology().n_directions(), 8);
+        assert_eq!(grid.width(), 2);
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid.at(0, 0), "𐑪");
+        assert_eq!(grid.at(1, 1), "𐑭");
+
+        assert_eq!(Grid::new("#").unwrap_err(), Error::EmptyGrid);
+    }
+
+    #[test]
+    fn multi_letter_tiles() {
+        let grid = Grid::new(
+            "a [𐑒𐑿] [QU]\n\
+             [ b c ] d [e]"
+        ).unwrap();
+
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid.at(0, 0), "𐑪");
+        assert_eq!(grid.at(1, 0), "𐑒𐑿");
+        assert_eq!(grid.at(2, 0), "𐑠𐑤");
+        assert_eq!(grid.at(0, 1), "𐑫𐑬");
+        assert_eq!(grid.at(1, 1), "𐑭");
+        assert_eq!(grid.at(2, 1), "𐑮");
+
+        assert_eq!(&grid.to_string(), "a[Cv][QU]:[bc]de");
+
+        assert_eq!(
+            Grid::new("a [bc").unwrap_err(),
+            Error::UnterminatedTile(Position { line: 1, column: 3 }),
+        );
+        assert_eq!(
+            Grid::new("[a:b]").unwrap_err(),
+            Error::UnterminatedTile(Position { line: 1, column: 1 }),
+        );
+        assert_eq!(
+            Grid::new("a\n [a\nb]").unwrap_err(),
+            Error::UnterminatedTile(Position { line: 2, column: 2 }),
+        );
+        assert_eq!(
+            Grid::new("a [ ] b").unwrap_err(),
+            Error::EmptyTile(Position { line: 1, column: 3 }),
+        );
+        assert_eq!(
+            &Grid::new("[").unwrap_err().to_string(),
+            "unterminated tile",
+        );
+        assert_eq!(&Grid::new("[]").unwrap_err().to_string(), "empty tile");
+    }
+
+    #[test]
+    fn positions() {
+        let grid = Grid::new("a b a : . [bc] c : a").unwrap();
+
+        assert_eq!(grid.positions('𐑪'), &[(0, 0), (2, 0), (0, 2)]);
+        assert_eq!(grid.positions('𐑫'), &[(1, 0), (1, 1)]);
+        assert_eq!(grid.positions('𐑬'), &[(2, 1)]);
+        assert!(grid.positions('.').is_empty());
+        assert!(grid.positions('𐑭').is_empty());
+
+        let mut letters = grid.letter_positions()
+            .map(|(letter, positions)| (letter, positions.len()))
+            .collect::<Vec<_>>();
+        letters.sort_unstable();
+        assert_eq!(&letters, &[('𐑪', 3), ('𐑫', 2), ('𐑬', 1)]);
+    }
+
+    #[test]
+    fn with_tile() {
+        let grid = Grid::new("#a b : c a").unwrap();
+        let changed = grid.with_tile(1, 0, "𐑪𐑭");
+
+        assert_eq!(grid.at(1, 0), "𐑫");
+        assert_eq!(changed.at(1, 0), "𐑪𐑭");
+        assert_eq!(changed.shape(), Shape::Square);
+        assert!(changed.positions('𐑫').is_empty());
+        assert_eq!(changed.positions('𐑪'), &[(0, 0), (1, 0), (1, 1)]);
+
+        let changed = changed.with_tile(0, 0, ".");
+        assert_eq!(changed.positions('𐑪'), &[(1, 0), (1, 1)]);
+        assert_eq!(&changed.to_string(), "#.[ad]:ca");
+    }
+
+    #[test]
+    fn report() {
+        let source = "a b\n\tc ? d";
+        let error = Grid::new(source).unwrap_err();
+
+        assert_eq!(
+            &error.report("puzzle.txt", source),
+            "puzzle.txt:2:4: error: invalid letter “?”\n\
+             2 | \tc ? d\n  \
+             | \t  ^",
+        );
+        assert_eq!(
+            &Error::EmptyGrid.report("puzzle.txt", ""),
+            "puzzle.txt: empty grid",
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Grid::new(". . :.").unwrap_err(), Error::EmptyGrid);
+        assert_eq!(Grid::new("#. .").unwrap_err(), Error::EmptyGrid);
+        assert!(Grid::new(". a .").is_ok());
+
+        let error = Grid::new("𐑐 𐑑\n𐑒 x 𐑓").unwrap_err();
+        assert_eq!(
+            error,
+            Error::InvalidLetter(Position { line: 2, column: 3 }, 'x'),
+        );
+        assert_eq!(error.position(), Some(Position { line: 2, column: 3 }));
+        assert_eq!(&error.to_string(), "invalid letter “x”");
+        assert_eq!(&error.position().unwrap().to_string(), "2:3");
+
+        // Colons don’t start a new line for the position
+        assert_eq!(
+            Grid::new("ab:c?").unwrap_err(),
+            Error::InvalidLetter(Position { line: 1, column: 5 }, '?'),
+        );
+        // Gaps aren’t allowed in multi-letter tiles
+        assert_eq!(
+            Grid::new("[a.]").unwrap_err(),
+            Error::InvalidLetter(Position { line: 1, column: 3 }, '.'),
+        );
+        // The square marker is only allowed at the start
+        assert_eq!(
+            Grid::new("a#").unwrap_err(),
+            Error::InvalidLetter(Position { line: 1, column: 2 }, '#'),
+        );
+        assert_eq!(
+            Grid::new(":#a").unwrap_err(),
+            Error::InvalidLetter(Position { line: 1, column: 2 }, '#'),
+        );
+
+        let row = "a ".repeat(MAX_WIDTH);
+        assert!(Grid::new(&row).is_ok());
+
+        let error = Grid::new(&format!("a\n{}b", row)).unwrap_err();
+        assert_eq!(
+            error,
+            Error::RowTooLong(Position { line: 2, column: MAX_WIDTH * 2 + 1 }),
+        );
+        assert_eq!(&error.to_string(), "row is longer than 32 tiles");
+        assert_eq!(Grid::new("").unwrap_err().position(), None);
     }
 }
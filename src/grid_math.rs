@@ -15,22 +15,24 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::grid::Grid;
+use super::directions::Shape;
 use std::f32::consts::PI;
+use std::fmt::Write;
 
 // Return the start and end of the grid in units of of half
-// hexagons. The odd rows can take up an extra half hexagon, but
-// sometimes this isn’t needed if the end as a blank.
+// tiles. On hexagonal grids the odd rows can take up an extra half
+// hexagon, but sometimes this isn’t needed if the end as a blank.
 fn half_grid_size(grid: &Grid) -> (u32, u32) {
     (0..grid.height()).map(|y| {
-        let first = (0..grid.width()).find(|&x| grid.at(x, y) != '.')
+        let first = (0..grid.width()).find(|&x| grid.at(x, y) != ".")
             .unwrap_or(grid.width() - 1) *
             2;
-        let last = ((0..grid.width()).rev().find(|&x| grid.at(x, y) != '.')
+        let last = ((0..grid.width()).rev().find(|&x| grid.at(x, y) != ".")
                     .unwrap_or(0) +
                     1) *
             2;
 
-        if y & 1 == 0 {
+        if y & 1 == 0 || grid.shape() == Shape::Square {
             (first, last)
         } else {
             (first + 1, last + 1)
@@ -44,22 +46,39 @@ fn half_grid_size(grid: &Grid) -> (u32, u32) {
 }
 
 pub struct Geometry {
+    pub shape: Shape,
     pub width: f32,
     pub height: f32,
-    // Coordinates of the center of the top left hexagon
+    // Coordinates of the center of the top left tile
     pub top_x: f32,
     pub top_y: f32,
-    // The outer radius of a hexagon
+    // The outer radius of a hexagon or half the side of a square
     pub radius: f32,
-    // Horizontal distance between the centres of hexagons
+    // Horizontal distance between the centres of tiles
     pub step_x: f32,
-    // Vertical dintance between the centres of hexagons
+    // Vertical dintance between the centres of tiles
     pub step_y: f32,
 }
 
 impl Geometry {
     pub fn new(grid: &Grid, viewport_width: f32) -> Geometry {
         let (first, last) = half_grid_size(grid);
+
+        if grid.shape() == Shape::Square {
+            let radius = viewport_width / (last - first) as f32;
+
+            return Geometry {
+                shape: Shape::Square,
+                width: viewport_width,
+                height: radius * 2.0 * grid.height() as f32,
+                top_x: radius - first as f32 * radius,
+                top_y: radius,
+                radius,
+                step_x: radius * 2.0,
+                step_y: radius * 2.0,
+            };
+        }
+
         // Number of apothems required for the width
         let width_in_apothems = (last - first) as f32;
         // The radius of a hexagon in units of apothems
@@ -73,6 +92,7 @@ impl Geometry {
         let radius = radius_in_apothems * apothem;
 
         Geometry {
+            shape: Shape::Hexagon,
             width: viewport_width,
             height: apothem * height_in_apothems,
             top_x: apothem - first as f32 * apothem,
@@ -83,9 +103,9 @@ impl Geometry {
         }
     }
 
-    // Calculate the centre of a hexagon in the grid
+    // Calculate the centre of a tile in the grid
     pub fn convert_coords(&self, x: u32, y: u32) -> (f32, f32) {
-        let x_off = if y & 1 == 0 {
+        let x_off = if y & 1 == 0 || self.shape == Shape::Square {
             0.0
         } else {
             self.step_x / 2.0
@@ -97,7 +117,7 @@ impl Geometry {
         )
     }
 
-    // Return the hexagon that covers the given coordinates, if there is one
+    // Return the tile that covers the given coordinates, if there is one
     pub fn reverse_coords(&self, x: f32, y: f32) -> (u32, u32) {
         if x < 0.0 || y < 0.0 {
             return (u32::MAX, u32::MAX);
@@ -108,6 +128,10 @@ impl Geometry {
         // Offset the x from the leftmost straight part of the grid
         let x = x - (self.top_x - self.step_x / 2.0);
 
+        if self.shape == Shape::Square {
+            return ((x / self.step_x) as u32, (y / self.step_y) as u32);
+        }
+
         // Half the height of the rectangular part in the middle of the hexagon
         let half_rect_height = self.radius * 0.5;
 
@@ -150,6 +174,39 @@ impl Geometry {
 
         (grid_x, grid_y)
     }
+
+    // SVG path for the outline of a tile centred on the origin
+    pub fn tile_path(&self) -> String {
+        let radius = self.radius;
+
+        if self.shape == Shape::Square {
+            return format!(
+                "M {} {} L {} {} L {} {} L {} {} z",
+                -radius, -radius,
+                radius, -radius,
+                radius, radius,
+                -radius, radius,
+            );
+        }
+
+        let mut result = String::new();
+
+        for i in 0..6 {
+            let angle = i as f32 * 2.0 * PI / 6.0;
+
+            write!(
+                &mut result,
+                "{} {} {} ",
+                if i == 0 { 'M' } else { 'L' },
+                radius * angle.sin(),
+                radius * -angle.cos(),
+            ).unwrap();
+        }
+
+        result.push('z');
+
+        result
+    }
 }
 
 #[cfg(test)]
@@ -194,6 +251,13 @@ mod test {
             ).unwrap()),
             (1, 7),
         );
+        assert_eq!(
+            half_grid_size(&Grid::new(
+                "#. a a\n\
+                  a a ."
+            ).unwrap()),
+            (0, 6),
+        );
     }
 
     #[test]
@@ -201,6 +265,10 @@ mod test {
         let grid = Grid::new("aaaa\naaa.").unwrap();
         let geometry = Geometry::new(&grid, 16.0);
 
+        assert!((geometry.width - 16.0).abs() < 0.01);
+        assert!(
+            (geometry.height - geometry.radius * 3.5).abs() < 0.01
+        );
         assert!((geometry.top_x - 2.0).abs() < 0.01);
         assert!((geometry.step_x - 4.0).abs() < 0.01);
         assert!((geometry.radius - (4.0 / 3.0f32.sqrt())).abs() < 0.01);
@@ -246,4 +314,42 @@ mod test {
         // Inside middle rectangle of hexagon
         assert_eq!(geometry.reverse_coords(8.03571, 28.34821), (4294967295, 1));
     }
+
+    #[test]
+    fn square() {
+        let grid = Grid::new("#.aa\naa.\naaa").unwrap();
+        let geometry = Geometry::new(&grid, 30.0);
+
+        assert_eq!(geometry.shape, Shape::Square);
+        assert!((geometry.width - 30.0).abs() < 0.01);
+        assert!((geometry.height - 30.0).abs() < 0.01);
+        assert!((geometry.radius - 5.0).abs() < 0.01);
+        assert!((geometry.step_x - 10.0).abs() < 0.01);
+        assert!((geometry.step_y - 10.0).abs() < 0.01);
+
+        // Odd rows aren’t shifted
+        assert_eq!(geometry.convert_coords(0, 0), (5.0, 5.0));
+        assert_eq!(geometry.convert_coords(1, 1), (15.0, 15.0));
+
+        assert_eq!(geometry.reverse_coords(1.0, 1.0), (0, 0));
+        assert_eq!(geometry.reverse_coords(19.0, 11.0), (1, 1));
+        assert_eq!(geometry.reverse_coords(29.0, 29.0), (2, 2));
+        assert_eq!(geometry.reverse_coords(-1.0, 5.0), (u32::MAX, u32::MAX));
+    }
+
+    #[test]
+    fn tile_path() {
+        let grid = Grid::new("#aa\naa").unwrap();
+        let geometry = Geometry::new(&grid, 4.0);
+
+        assert_eq!(&geometry.tile_path(), "M -1 -1 L 1 -1 L 1 1 L -1 1 z");
+
+        let grid = Grid::new("aa\naa").unwrap();
+        let geometry = Geometry::new(&grid, 10.0);
+        let path = geometry.tile_path();
+
+        assert!(path.starts_with("M 0 -"));
+        assert!(path.ends_with('z'));
+        assert_eq!(path.matches('L').count(), 5);
+    }
 }
@@ -0,0 +1,190 @@
+// Wordroute – A word game
+// Copyright (C) 2024  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// A stack of dictionaries that can be walked as if it were a single
+// trie. A word is in the combined dictionary if it is in any of the
+// layers and it isn’t in the list of removed words.
+
+use super::dictionary::{Dictionary, Walker};
+
+pub struct LayeredDictionary {
+    layers: Vec<Dictionary>,
+    removed_words: Option<Dictionary>,
+}
+
+// The walker for the first layer that still has the prefix is kept
+// separately so that walking a single dictionary never needs to
+// allocate. This is in the innermost loop of the word search.
+#[derive(Clone)]
+pub struct LayeredWalker<'a> {
+    first: Walker<'a>,
+    rest: Vec<Walker<'a>>,
+    removed_words: Option<Walker<'a>>,
+}
+
+impl LayeredDictionary {
+    pub fn new<I, T>(
+        layers: Vec<Dictionary>,
+        removed_words: I,
+    ) -> LayeredDictionary
+        where I: IntoIterator<Item = T>,
+              T: AsRef<str>
+    {
+        let mut removed_words = removed_words.into_iter().peekable();
+
+        let removed_words = removed_words.peek().is_some().then(|| {
+            Dictionary::from_words(removed_words)
+        });
+
+        LayeredDictionary {
+            layers,
+            removed_words,
+        }
+    }
+
+    // Get the value stored for the word by the first layer that has a
+    // value for it
+    pub fn value(&self, word: &str) -> Option<u32> {
+        let walker = LayeredWalker::new(self)?;
+        let walker = word.chars().try_fold(walker, |w, ch| w.step(ch))?;
+
+        if walker.is_end() {
+            walker.walkers().find_map(|w| w.value())
+        } else {
+            None
+        }
+    }
+}
+
+impl From<Dictionary> for LayeredDictionary {
+    fn from(dictionary: Dictionary) -> LayeredDictionary {
+        LayeredDictionary::new(vec![dictionary], std::iter::empty::<&str>())
+    }
+}
+
+impl<'a> LayeredWalker<'a> {
+    pub fn new(dictionary: &LayeredDictionary) -> Option<LayeredWalker<'_>> {
+        let mut walkers = dictionary.layers.iter().filter_map(Walker::new);
+
+        Some(LayeredWalker {
+            first: walkers.next()?,
+            rest: walkers.collect(),
+            removed_words: dictionary.removed_words.as_ref()
+                .and_then(Walker::new),
+        })
+    }
+
+    fn walkers(&self) -> impl Iterator<Item = &Walker<'a>> {
+        std::iter::once(&self.first).chain(self.rest.iter())
+    }
+
+    pub fn is_end(&self) -> bool {
+        self.walkers().any(|w| w.is_end()) &&
+            !self.removed_words.as_ref().is_some_and(|w| w.is_end())
+    }
+
+    pub fn step(&self, next: char) -> Option<LayeredWalker<'a>> {
+        let mut walkers = self.walkers().filter_map(|w| w.step(next));
+
+        Some(LayeredWalker {
+            first: walkers.next()?,
+            // Collecting nothing doesn’t allocate
+            rest: walkers.collect(),
+            removed_words: self.removed_words.as_ref()
+                .and_then(|w| w.step(next)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::dictionary::Builder;
+
+    fn walk<'a>(
+        dictionary: &'a LayeredDictionary,
+        word: &str,
+    ) -> Option<LayeredWalker<'a>> {
+        word.chars().try_fold(
+            LayeredWalker::new(dictionary)?,
+            |w, ch| w.step(ch),
+        )
+    }
+
+    fn contains(dictionary: &LayeredDictionary, word: &str) -> bool {
+        walk(dictionary, word).is_some_and(|w| w.is_end())
+    }
+
+    #[test]
+    fn layers() {
+        let dictionary = LayeredDictionary::new(
+            vec![
+                Dictionary::from_words(["pasi", "kuri", "kuris"]),
+                Dictionary::from_words(["pasis", "kanti"]),
+            ],
+            ["kuri", "pasis", "notinthere"],
+        );
+
+        assert!(contains(&dictionary, "pasi"));
+        assert!(contains(&dictionary, "kuris"));
+        assert!(contains(&dictionary, "kanti"));
+        assert!(!contains(&dictionary, "kuri"));
+        assert!(!contains(&dictionary, "pasis"));
+        assert!(!contains(&dictionary, "notinthere"));
+        assert!(!contains(&dictionary, "kan"));
+
+        // Removing a word shouldn’t prune its prefix
+        assert!(walk(&dictionary, "kur").is_some());
+        assert!(walk(&dictionary, "kuz").is_none());
+    }
+
+    #[test]
+    fn values() {
+        let mut builder = Builder::new();
+        builder.add_word_with_value("pasi", 3);
+        builder.add_word_with_value("kanti", 5);
+
+        let mut second_builder = Builder::new();
+        second_builder.add_word_with_value("kanti", 7);
+
+        let dictionary = LayeredDictionary::new(
+            vec![
+                Dictionary::from_words(["kuri", "pasi"]),
+                Dictionary::new(builder.build()),
+                Dictionary::new(second_builder.build()),
+            ],
+            ["kuri"],
+        );
+
+        // The first layer has pasi without a value so the value comes
+        // from the second layer
+        assert_eq!(dictionary.value("pasi"), Some(3));
+        assert_eq!(dictionary.value("kanti"), Some(5));
+        assert_eq!(dictionary.value("kuri"), None);
+        assert_eq!(dictionary.value("pas"), None);
+    }
+
+    #[test]
+    fn empty() {
+        let dictionary = LayeredDictionary::new(
+            vec![Dictionary::from_words(std::iter::empty::<&str>())],
+            ["removed"],
+        );
+
+        assert!(LayeredWalker::new(&dictionary).is_none());
+        assert!(dictionary.value("removed").is_none());
+    }
+}
@@ -0,0 +1,102 @@
+// Wordroute – A word game
+// Copyright (C) 2024  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Helpers for the command-line tools to load the dictionaries and the
+// lists of words
+
+use super::dictionary::Dictionary;
+use super::layered_dictionary::LayeredDictionary;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+fn read_word_list_from_file<P: AsRef<Path>>(
+    filename: P,
+    words: &mut HashSet<String>,
+) -> Result<(), std::io::Error> {
+    for line in BufReader::new(fs::File::open(filename)?).lines() {
+        let line = line?;
+        let line = line.trim();
+
+        if !line.is_empty() && !line.starts_with('#') {
+            words.insert(line.to_string());
+        }
+    }
+
+    Ok(())
+}
+
+pub fn read_word_list<I, P>(
+    filenames: I,
+) -> Result<HashSet<String>, std::io::Error>
+    where I: IntoIterator<Item = P>,
+          P: AsRef<Path>,
+{
+    let mut words = HashSet::new();
+
+    for filename in filenames {
+        read_word_list_from_file(&filename, &mut words)
+            .map_err(|e| {
+                let kind = e.kind();
+                std::io::Error::new(
+                    kind,
+                    format!(
+                        "{}: {}",
+                        filename.as_ref().to_string_lossy(),
+                        e,
+                    ))
+            })?;
+    }
+
+    Ok(words)
+}
+
+pub fn load_dictionary(
+    filename: &OsString,
+) -> Result<Dictionary, String> {
+    fs::read(filename)
+        .map_err(|e| e.to_string())
+        .and_then(|data| {
+            Dictionary::new_checked(data.into_boxed_slice())
+                .map_err(|e| e.to_string())
+        })
+        .map_err(|e| format!("{}: {}", filename.to_string_lossy(), e))
+}
+
+// Loads the dictionaries as layers with any extra words on top and
+// then takes away the removed words
+pub fn load_dictionaries(
+    dictionaries: &[OsString],
+    add_words: &[OsString],
+    remove_words: &[OsString],
+) -> Result<LayeredDictionary, String> {
+    let mut layers = dictionaries.iter()
+        .map(load_dictionary)
+        .collect::<Result<Vec<_>, String>>()?;
+
+    if !add_words.is_empty() {
+        let added_words = read_word_list(add_words.iter())
+            .map_err(|e| e.to_string())?;
+        layers.push(Dictionary::from_words(added_words));
+    }
+
+    let removed_words = read_word_list(remove_words.iter())
+        .map_err(|e| e.to_string())?;
+
+    Ok(LayeredDictionary::new(layers, removed_words))
+}
@@ -0,0 +1,90 @@
+// Wordroute – A word game
+// Copyright (C) 2024  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+#[allow(dead_code)]
+mod dictionary;
+
+use std::process::ExitCode;
+use std::io::{BufRead, Write};
+use std::ffi::OsString;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(name = "MakeDictionary")]
+struct Cli {
+    #[arg(value_name = "OUTPUT")]
+    output: OsString,
+}
+
+// Each line is a word optionally followed by whitespace and a number
+// to store as the word’s value, such as its frequency
+fn read_words(
+    builder: &mut dictionary::Builder,
+) -> Result<(), String> {
+    for (line_num, line) in std::io::stdin().lock().lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        let mut parts = line.split_whitespace();
+
+        let Some(word) = parts.next()
+        else {
+            continue;
+        };
+
+        match parts.next() {
+            None => builder.add_word(word),
+            Some(value) => {
+                let Ok(value) = value.parse::<u32>()
+                else {
+                    return Err(format!(
+                        "line {}: invalid value “{}”",
+                        line_num + 1,
+                        value,
+                    ));
+                };
+
+                builder.add_word_with_value(word, value);
+            },
+        }
+
+        if parts.next().is_some() {
+            return Err(format!("line {}: trailing text", line_num + 1));
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let mut builder = dictionary::Builder::new();
+
+    if let Err(e) = read_words(&mut builder) {
+        eprintln!("{}", e);
+        return ExitCode::FAILURE;
+    }
+
+    let data = builder.build();
+
+    if let Err(e) = std::fs::File::create(&cli.output)
+        .and_then(|mut file| file.write_all(&data))
+    {
+        eprintln!("{}: {}", cli.output.to_string_lossy(), e);
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,366 @@
+// Wordroute – A word game
+// Copyright (C) 2024  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Lays out a puzzle on two A4 pages so that it can be played on
+// paper. The first page has the grid and a row of empty boxes for each
+// word, grouped by length. The second page has the answers.
+
+use super::puzzle_data::{PuzzleData, WordType};
+use super::grid_math::Geometry;
+use super::svg;
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+
+// All of the sizes are in millimetres
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const GRID_WIDTH: f32 = 100.0;
+const TITLE_SIZE: f32 = 8.0;
+const HEADING_SIZE: f32 = 4.5;
+// The boxes are made smaller if the slots don’t fit on the page
+const BOX_SIZES: [f32; 5] = [5.0, 4.5, 4.0, 3.5, 3.0];
+const ANSWER_SIZE: f32 = 4.0;
+const N_ANSWER_COLUMNS: usize = 5;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    TooManyWords,
+    TooManyAnswers,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::TooManyWords => {
+                write!(
+                    f,
+                    "the boxes for the words don’t fit on the page even at \
+                     the smallest size",
+                )
+            },
+            Error::TooManyAnswers => {
+                write!(f, "the answers don’t fit on the page")
+            },
+        }
+    }
+}
+
+// Each page is a separate SVG image
+pub struct Pages {
+    pub puzzle: String,
+    pub answers: String,
+}
+
+fn write_text(
+    svg: &mut String,
+    x: f32,
+    y: f32,
+    font_size: f32,
+    anchor: &str,
+    text: &str,
+) {
+    write!(
+        svg,
+        "  <text text-anchor=\"{}\" font-family=\"sans-serif\" \
+         x=\"{}\" y=\"{}\" font-size=\"{}\">",
+        anchor,
+        x,
+        y,
+        font_size,
+    ).unwrap();
+    svg::write_escaped(svg, text);
+    svg.push_str("</text>\n");
+}
+
+// Starts a new page with the title at the top. Returns the position
+// below the title.
+fn start_page(svg: &mut String, title: &str) -> f32 {
+    writeln!(
+        svg,
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n\
+         <svg version=\"1.1\" width=\"{0}mm\" height=\"{1}mm\" \
+         viewBox=\"0 0 {0} {1}\" xmlns=\"http://www.w3.org/2000/svg\">",
+        PAGE_WIDTH,
+        PAGE_HEIGHT,
+    ).unwrap();
+
+    let title_y = MARGIN + TITLE_SIZE;
+
+    write_text(svg, PAGE_WIDTH / 2.0, title_y, TITLE_SIZE, "middle", title);
+
+    title_y + TITLE_SIZE
+}
+
+fn write_heading(svg: &mut String, y: f32, text: &str) -> f32 {
+    write_text(svg, MARGIN, y + HEADING_SIZE, HEADING_SIZE, "start", text);
+    y + HEADING_SIZE * 2.0
+}
+
+// Draws a row of boxes with one box for each letter
+fn write_slot(
+    svg: &mut String,
+    x: f32,
+    y: f32,
+    length: usize,
+    box_size: f32,
+) {
+    let mut path_d = format!(
+        "M {} {} h {} v {} h {} z",
+        x,
+        y,
+        length as f32 * box_size,
+        box_size,
+        -(length as f32) * box_size,
+    );
+
+    for i in 1..length {
+        write!(
+            &mut path_d,
+            " M {} {} v {}",
+            x + i as f32 * box_size,
+            y,
+            box_size,
+        ).unwrap();
+    }
+
+    writeln!(
+        svg,
+        "  <path d=\"{}\" fill=\"none\" stroke=\"#333\" \
+         stroke-width=\"0.3\" />",
+        path_d,
+    ).unwrap();
+}
+
+// Returns the position below the last slot
+fn write_slots(
+    svg: &mut String,
+    mut y: f32,
+    lengths: &BTreeMap<usize, Vec<&str>>,
+    box_size: f32,
+) -> f32 {
+    let gap = box_size * 0.8;
+
+    for (&length, words) in lengths.iter() {
+        y = write_heading(svg, y, &format!("{} letters", length));
+
+        let slot_width = length as f32 * box_size;
+        let mut x = MARGIN;
+
+        for _ in 0..words.len() {
+            if x > MARGIN && x + slot_width > PAGE_WIDTH - MARGIN {
+                x = MARGIN;
+                y += box_size + gap;
+            }
+
+            write_slot(svg, x, y, length, box_size);
+
+            x += slot_width + gap;
+        }
+
+        y += box_size + gap * 2.0;
+    }
+
+    y
+}
+
+fn write_answer_list(svg: &mut String, mut y: f32, words: &[&str]) -> f32 {
+    let column_width = (PAGE_WIDTH - MARGIN * 2.0) / N_ANSWER_COLUMNS as f32;
+
+    for row in words.chunks(N_ANSWER_COLUMNS) {
+        y += ANSWER_SIZE * 1.5;
+
+        for (column, word) in row.iter().enumerate() {
+            write_text(
+                svg,
+                MARGIN + column as f32 * column_width,
+                y,
+                ANSWER_SIZE,
+                "start",
+                word,
+            );
+        }
+    }
+
+    y + ANSWER_SIZE * 2.0
+}
+
+// Returns the position below the last answer
+fn write_answers(
+    svg: &mut String,
+    mut y: f32,
+    lengths: &BTreeMap<usize, Vec<&str>>,
+    bonus_words: &[&str],
+) -> f32 {
+    for (&length, words) in lengths.iter() {
+        y = write_heading(svg, y, &format!("{} letters", length));
+        y = write_answer_list(svg, y, words);
+    }
+
+    if !bonus_words.is_empty() {
+        y = write_heading(svg, y, "Bonus words");
+        y = write_answer_list(svg, y, bonus_words);
+    }
+
+    y
+}
+
+pub fn puzzle_to_svg(
+    puzzle_data: &PuzzleData,
+    title: &str,
+) -> Result<Pages, Error> {
+    let grid = &puzzle_data.grid;
+    let geometry = Geometry::new(grid, GRID_WIDTH);
+
+    let mut lengths = BTreeMap::<usize, Vec<&str>>::new();
+    let mut bonus_words = Vec::new();
+
+    for (word, word_type) in puzzle_data.words.iter() {
+        match word_type {
+            WordType::Normal | WordType::Featured => {
+                lengths.entry(word.chars().count())
+                    .or_default()
+                    .push(word);
+            },
+            WordType::Bonus => bonus_words.push(word.as_str()),
+            WordType::Excluded => (),
+        }
+    }
+
+    let mut puzzle = String::new();
+
+    let grid_y = start_page(&mut puzzle, title);
+
+    writeln!(
+        puzzle,
+        "  <g transform=\"translate({}, {})\">",
+        (PAGE_WIDTH - GRID_WIDTH) / 2.0,
+        grid_y,
+    ).unwrap();
+    svg::write_tiles(&mut puzzle, grid, &geometry, None);
+    puzzle.push_str("  </g>\n");
+
+    let slots_y = grid_y + geometry.height + TITLE_SIZE;
+
+    // Use the biggest boxes that fit by trying to lay them out first
+    let box_size = BOX_SIZES.into_iter().find(|&box_size| {
+        write_slots(&mut String::new(), slots_y, &lengths, box_size) <=
+            PAGE_HEIGHT - MARGIN
+    }).ok_or(Error::TooManyWords)?;
+
+    write_slots(&mut puzzle, slots_y, &lengths, box_size);
+
+    puzzle.push_str("</svg>\n");
+
+    let mut answers = String::new();
+
+    let answers_y = start_page(&mut answers, "Answers");
+
+    if write_answers(&mut answers, answers_y, &lengths, &bonus_words) >
+        PAGE_HEIGHT - MARGIN
+    {
+        return Err(Error::TooManyAnswers);
+    }
+
+    answers.push_str("</svg>\n");
+
+    Ok(Pages { puzzle, answers })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn puzzle() {
+        let puzzle_data = "#𐑒𐑨𐑚:𐑿𐑑.,𐑑𐑨𐑒𐑿,𐑒𐑨𐑚,𐑚𐑨𐑑:b,𐑚𐑨𐑒:x,𐑚𐑨𐑑𐑿"
+            .parse::<PuzzleData>()
+            .unwrap();
+        let pages = puzzle_to_svg(&puzzle_data, "WordRoute #1").unwrap();
+
+        for page in [&pages.puzzle, &pages.answers] {
+            assert!(page.starts_with("<?xml"));
+            assert!(page.contains("width=\"210mm\" height=\"297mm\""));
+            assert!(page.ends_with("</svg>\n"));
+            assert_eq!(page.matches("<svg ").count(), 1);
+        }
+
+        let puzzle = &pages.puzzle;
+
+        assert!(puzzle.contains(">WordRoute #1</text>"));
+        assert!(puzzle.contains(">3 letters</text>"));
+        assert!(puzzle.contains(">4 letters</text>"));
+        assert!(!puzzle.contains(">Bonus words</text>"));
+        // One slot for each normal word
+        assert_eq!(puzzle.matches("stroke=\"#333\"").count(), 3);
+        assert!(!puzzle.contains(">𐑒𐑨𐑚</text>"));
+
+        let answers = &pages.answers;
+
+        assert!(answers.contains(">Answers</text>"));
+        assert!(answers.contains(">3 letters</text>"));
+        assert!(answers.contains(">Bonus words</text>"));
+        assert!(answers.contains(">𐑒𐑨𐑚</text>"));
+        assert!(answers.contains(">𐑚𐑨𐑑</text>"));
+        assert!(!answers.contains(">𐑚𐑨𐑒</text>"));
+    }
+
+    #[test]
+    fn escape_title() {
+        let puzzle_data = "#𐑒𐑨𐑚:𐑿𐑑.,𐑒𐑨𐑚".parse::<PuzzleData>().unwrap();
+        let pages = puzzle_to_svg(&puzzle_data, "<Cats & \"Bats\">")
+            .unwrap();
+
+        assert!(pages.puzzle.contains(
+            ">&lt;Cats &amp; &quot;Bats&quot;&gt;</text>"
+        ));
+    }
+
+    #[test]
+    fn overflow() {
+        let grid = "#𐑒𐑨𐑚:𐑿𐑑.";
+
+        let puzzle_data = PuzzleData {
+            grid: grid.parse::<PuzzleData>().unwrap().grid,
+            words: vec![("𐑒𐑨𐑚𐑿𐑑𐑒𐑨𐑚𐑿𐑑".to_string(), WordType::Normal); 400],
+        };
+        assert_eq!(
+            puzzle_to_svg(&puzzle_data, "").err(),
+            Some(Error::TooManyWords),
+        );
+
+        // Bonus words don’t have any boxes but they are in the answers
+        let puzzle_data = PuzzleData {
+            grid: grid.parse::<PuzzleData>().unwrap().grid,
+            words: vec![("𐑒𐑨𐑚".to_string(), WordType::Bonus); 400],
+        };
+        assert_eq!(
+            puzzle_to_svg(&puzzle_data, "").err(),
+            Some(Error::TooManyAnswers),
+        );
+    }
+
+    #[test]
+    fn slot() {
+        let mut svg = String::new();
+
+        write_slot(&mut svg, 1.0, 2.0, 3, 5.0);
+
+        assert!(svg.contains(
+            "d=\"M 1 2 h 15 v 5 h -15 z M 6 2 v 5 M 11 2 v 5\""
+        ));
+    }
+}
@@ -0,0 +1,99 @@
+// Wordroute – A word game
+// Copyright (C) 2024  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+#[allow(dead_code)]
+mod grid;
+#[allow(dead_code)]
+mod grid_math;
+#[allow(dead_code)]
+mod directions;
+#[allow(dead_code)]
+mod counts;
+#[allow(dead_code)]
+mod puzzle_data;
+#[allow(dead_code)]
+mod shavicode;
+#[allow(dead_code)]
+mod svg;
+mod paper;
+
+use std::process::ExitCode;
+use std::ffi::OsString;
+use clap::Parser;
+use puzzle_data::PuzzleData;
+
+#[derive(Parser)]
+#[command(name = "PrintPuzzle")]
+struct Cli {
+    /// A line from puzzles.txt
+    #[arg(value_name = "PUZZLE")]
+    puzzle: String,
+    /// Title to print above the grid
+    #[arg(short, long, value_name = "TITLE", default_value = "WordRoute")]
+    title: String,
+    /// File to save the page with the puzzle to. Defaults to the
+    /// standard output
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<OsString>,
+    /// File to save the page with the answers to. The answers aren’t
+    /// saved if this isn’t given
+    #[arg(short, long, value_name = "FILE")]
+    answers: Option<OsString>,
+}
+
+fn write_page(filename: &OsString, svg: &str) -> Result<(), String> {
+    std::fs::write(filename, svg)
+        .map_err(|e| format!("{}: {}", filename.to_string_lossy(), e))
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let puzzle_data = match cli.puzzle.trim().parse::<PuzzleData>() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        },
+    };
+
+    let pages = match paper::puzzle_to_svg(&puzzle_data, &cli.title) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        },
+    };
+
+    if let Some(filename) = cli.answers {
+        if let Err(e) = write_page(&filename, &pages.answers) {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    match cli.output {
+        Some(filename) => {
+            if let Err(e) = write_page(&filename, &pages.puzzle) {
+                eprintln!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => print!("{}", pages.puzzle),
+    }
+
+    ExitCode::SUCCESS
+}
@@ -17,7 +17,6 @@
 use super::grid::Grid;
 use super::counts::GridCounts;
 use super::word_finder;
-use super::directions;
 use super::save_state::SaveState;
 use super::puzzle_data::{PuzzleData, WordType};
 use std::collections::{hash_map, HashMap, HashSet};
@@ -66,10 +65,14 @@ pub struct Puzzle {
     pending_excluded_word: bool,
     pending_finish: bool,
 
-    counts_dirty: u64,
+    near_misses_enabled: bool,
+
+    // Bitmasks with one bit for each tile and one bit for each word
+    // length
+    counts_dirty: Vec<u64>,
     n_words_found_dirty: bool,
     n_letters_found_dirty: bool,
-    word_lists_dirty: u64,
+    word_lists_dirty: Vec<u64>,
     hint_level_dirty: bool,
     save_state_dirty: bool,
 }
@@ -88,21 +91,24 @@ impl Puzzle {
             .collect::<HashMap<_, _>>();
 
         let total_n_words = words.values().filter(|w| {
-            w.word_type == WordType::Normal
+            w.word_type.is_normal()
         }).count();
 
         let total_n_letters = words.values().filter_map(|w| {
-            (w.word_type == WordType::Normal).then_some(w.length)
+            w.word_type.is_normal().then_some(w.length)
         }).sum::<usize>();
 
-        let counts_dirty = u64::MAX >>
-            (u64::BITS - data.grid.width() * data.grid.height());
+        let mut counts_dirty = Vec::new();
+
+        for tile in 0..(data.grid.width() * data.grid.height()) as usize {
+            set_bit(&mut counts_dirty, tile);
+        }
 
-        let mut word_lists_dirty = 0;
+        let mut word_lists_dirty = Vec::new();
 
         for word in words.values() {
-            if word.word_type == WordType::Normal {
-                word_lists_dirty |= 1 << word.length;
+            if word.word_type.is_normal() {
+                set_bit(&mut word_lists_dirty, word.length);
             }
         }
 
@@ -114,7 +120,7 @@ impl Puzzle {
             &mut word_finder,
             &mut route_buf,
             words.iter().filter_map(|(key, word)| {
-                (word.word_type == WordType::Normal).then_some(key)
+                word.word_type.is_normal().then_some(key)
             }),
         );
 
@@ -138,6 +144,8 @@ impl Puzzle {
             pending_excluded_word: false,
             pending_finish: false,
 
+            near_misses_enabled: false,
+
             counts_dirty,
             n_words_found_dirty: true,
             n_letters_found_dirty: true,
@@ -165,7 +173,7 @@ impl Puzzle {
             if let Some((word, word_data)) = sorted_words.get_mut(word_num) {
                 if !word_data.found {
                     word_data.found = true;
-                    if word_data.word_type == WordType::Normal {
+                    if word_data.word_type.is_normal() {
                         words_to_score.push(
                             (word.to_string(), word_data.length)
                         );
@@ -194,7 +202,7 @@ impl Puzzle {
         self.n_letters_found += length;
         self.n_letters_found_dirty = true;
         self.update_hint_level();
-        self.word_lists_dirty |= 1 << length;
+        set_bit(&mut self.word_lists_dirty, length);
     }
 
     pub fn score_word(&mut self, word: &str) {
@@ -210,7 +218,7 @@ impl Puzzle {
                     WordType::Bonus => {
                         self.show_word_message("Already found (bonus)");
                     },
-                    WordType::Normal => {
+                    WordType::Normal | WordType::Featured => {
                         self.show_word_message("Already found");
                     }
                     WordType::Excluded => self.pending_excluded_word = true,
@@ -218,10 +226,21 @@ impl Puzzle {
             } else {
                 self.save_state_dirty = true;
 
+                let prefix = match word_data.word_type {
+                    WordType::Featured => "Every tile! ",
+                    _ => "",
+                };
+
                 match word_data.word_type {
                     WordType::Bonus => self.show_word_message("Bonus word!"),
-                    WordType::Normal => {
-                        show_word_message!(self, "+{} points!", length);
+                    WordType::Normal | WordType::Featured => {
+                        show_word_message!(
+                            self,
+                            "{}+{} points!",
+                            prefix,
+                            length
+                        );
+
                         self.score_normal_word(word, length);
 
                         if self.n_words_found >= self.total_n_words {
@@ -232,12 +251,36 @@ impl Puzzle {
                 }
             }
         } else {
-            self.show_word_message("Not in list");
+            if self.near_misses_enabled && self.near_miss(word).is_some() {
+                self.show_word_message("Close! One letter off a word");
+            } else {
+                self.show_word_message("Not in list");
+            }
             self.misses += 1;
             self.save_state_dirty = true;
         }
     }
 
+    // If this is enabled then guessing a word that isn’t in the list
+    // but is one letter off from a word that hasn’t been found yet
+    // will give a different message.
+    pub fn set_near_misses_enabled(&mut self, enabled: bool) {
+        self.near_misses_enabled = enabled;
+    }
+
+    // Find a normal word that hasn’t been found yet and that is one
+    // letter off from the given word
+    pub fn near_miss(&self, word: &str) -> Option<&str> {
+        self.words.iter()
+            .filter(|(key, word_data)| {
+                word_data.word_type.is_normal() &&
+                    !word_data.found &&
+                    is_one_letter_off(key, word)
+            })
+            .map(|(key, _)| key.as_str())
+            .min()
+    }
+
     fn update_hint_level(&mut self) {
         let new_hint_level = self.n_letters_found *
             N_HINT_LEVELS /
@@ -250,7 +293,10 @@ impl Puzzle {
     }
 
     fn dirty_counts_at_pos(&mut self, x: u32, y: u32) {
-        self.counts_dirty |= 1 << (y * self.grid.width() + x);
+        set_bit(
+            &mut self.counts_dirty,
+            (y * self.grid.width() + x) as usize,
+        );
     }
 
     fn remove_visits_for_word(&mut self, word: &str) {
@@ -260,7 +306,7 @@ impl Puzzle {
 
         if let Some((mut x, mut y)) = self.word_finder.find(
             &self.grid,
-            &word,
+            word,
             &mut route_buf,
         ) {
             let start = self.counts.at_mut(x, y);
@@ -269,7 +315,7 @@ impl Puzzle {
             self.dirty_counts_at_pos(x, y);
 
             for &dir in route_buf.iter() {
-                (x, y) = directions::step(x, y, dir);
+                (x, y) = self.grid.topology().step(x, y, dir);
 
                 self.counts.at_mut(x, y).visits -= 1;
 
@@ -398,7 +444,7 @@ impl Puzzle {
         lengths
     }
 
-    pub fn words(&self) -> Words {
+    pub fn words(&self) -> Words<'_> {
         Words::new(self.words.iter())
     }
 
@@ -423,6 +469,14 @@ impl Puzzle {
             }
         }
 
+        let found_featured_word = self.words.values().any(|word| {
+            word.word_type == WordType::Featured && word.found
+        });
+
+        if found_featured_word {
+            text.push_str("\n🐝 Found the word that uses every tile");
+        }
+
         if self.n_words_found >= self.total_n_words {
             if !self.hints_used {
                 text.push_str("\n😎 No hints used");
@@ -448,16 +502,58 @@ impl Puzzle {
     }
 }
 
+fn set_bit(bits: &mut Vec<u64>, bit: usize) {
+    let pos = bit / u64::BITS as usize;
+
+    if pos >= bits.len() {
+        bits.resize(pos + 1, 0);
+    }
+
+    bits[pos] |= 1 << (bit % u64::BITS as usize);
+}
+
+struct SetBits {
+    bits: Vec<u64>,
+    pos: usize,
+}
+
+impl SetBits {
+    fn new(bits: Vec<u64>) -> SetBits {
+        SetBits {
+            bits,
+            pos: 0,
+        }
+    }
+}
+
+impl Iterator for SetBits {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            let part = self.bits.get_mut(self.pos)?;
+
+            if *part == 0 {
+                self.pos += 1;
+            } else {
+                let index = part.trailing_zeros();
+                *part &= !(1 << index);
+                break Some(self.pos * u64::BITS as usize + index as usize);
+            }
+        }
+    }
+}
+
 pub struct ChangedCounts {
     grid_width: u32,
-    counts_dirty: u64,
+    counts_dirty: SetBits,
 }
 
 impl ChangedCounts {
-    fn new(grid_width: u32, counts_dirty: u64) -> ChangedCounts {
+    fn new(grid_width: u32, counts_dirty: Vec<u64>) -> ChangedCounts {
         ChangedCounts {
             grid_width,
-            counts_dirty,
+            counts_dirty: SetBits::new(counts_dirty),
         }
     }
 }
@@ -466,27 +562,21 @@ impl Iterator for ChangedCounts {
     type Item = (u32, u32);
 
     fn next(&mut self) -> Option<(u32, u32)> {
-        if self.counts_dirty == 0 {
-            None
-        } else {
-            let index = self.counts_dirty.trailing_zeros();
-            self.counts_dirty &= u64::MAX.wrapping_shl(index).wrapping_shl(1);
-            Some((
-                index as u32 % self.grid_width,
-                index as u32 / self.grid_width
-            ))
-        }
+        self.counts_dirty.next().map(|index| {
+            let index = index as u32;
+            (index % self.grid_width, index / self.grid_width)
+        })
     }
 }
 
 pub struct ChangedWordLists {
-    lists_dirty: u64,
+    lists_dirty: SetBits,
 }
 
 impl ChangedWordLists {
-    fn new(lists_dirty: u64) -> ChangedWordLists {
+    fn new(lists_dirty: Vec<u64>) -> ChangedWordLists {
         ChangedWordLists {
-            lists_dirty,
+            lists_dirty: SetBits::new(lists_dirty),
         }
     }
 }
@@ -495,13 +585,7 @@ impl Iterator for ChangedWordLists {
     type Item = usize;
 
     fn next(&mut self) -> Option<usize> {
-        if self.lists_dirty == 0 {
-            None
-        } else {
-            let index = self.lists_dirty.trailing_zeros();
-            self.lists_dirty &= u64::MAX.wrapping_shl(index).wrapping_shl(1);
-            Some(index as usize)
-        }
+        self.lists_dirty.next()
     }
 }
 
@@ -518,13 +602,48 @@ impl<'a> Iterator for Words<'a> {
 }
 
 impl<'a> Words<'a> {
-    fn new(inner: hash_map::Iter<'a, String, Word>) -> Words {
+    fn new(inner: hash_map::Iter<'a, String, Word>) -> Words<'a> {
         Words {
             inner
         }
     }
 }
 
+// Returns true if the two words differ by exactly one letter being
+// changed, added or removed
+fn is_one_letter_off(a: &str, b: &str) -> bool {
+    let a_length = a.chars().count();
+    let b_length = b.chars().count();
+
+    let (short, long, short_length, long_length) = if a_length <= b_length {
+        (a, b, a_length, b_length)
+    } else {
+        (b, a, b_length, a_length)
+    };
+
+    match long_length - short_length {
+        0 => {
+            short.chars().zip(long.chars()).filter(|(a, b)| a != b).count() ==
+                1
+        },
+        1 => {
+            let prefix_length = short.chars()
+                .zip(long.chars())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a.len_utf8())
+                .sum::<usize>();
+            let skipped = long[prefix_length..].chars().next().unwrap();
+
+            short[prefix_length..] ==
+                long[prefix_length + skipped.len_utf8()..]
+        },
+        _ => false,
+    }
+}
+
+// Words with more than one route are only counted with the first
+// route that the word finder returns. remove_visits_for_word uses the
+// same route so the counts will always go back down to zero.
 fn generate_counts<I, T>(
     grid: &Grid,
     word_finder: &mut word_finder::Finder,
@@ -549,7 +668,7 @@ fn generate_counts<I, T>(
             start.visits += 1;
 
             for &dir in route_buf.iter() {
-                (x, y) = directions::step(x, y, dir);
+                (x, y) = grid.topology().step(x, y, dir);
 
                 counts.at_mut(x, y).visits += 1;
             }
@@ -567,8 +686,8 @@ mod test {
         let grid = Grid::new(
             "𐑐𐑩𐑑𐑱𐑑𐑴𐑕𐑑𐑪𐑥𐑐𐑢𐑪𐑓𐑩𐑤\n\
              abcdefghijklmnop\n\
-             xxxxxxxxxxxxxxxx\n\
-             yyyyyyyyyyyyyyyy"
+             qqqqqqqqqqqqqqqq\n\
+             rrrrrrrrrrrrrrrr"
         ).unwrap();
 
         Puzzle::new(PuzzleData {
@@ -599,7 +718,7 @@ mod test {
 
         assert_eq!(
             &puzzle.changed_counts().collect::<Vec<_>>(),
-            &(0..4).map(|y| (0..16).map(move |x| (x, y))).flatten()
+            &(0..4).flat_map(|y| (0..16).map(move |x| (x, y)))
                 .collect::<Vec<_>>(),
         );
 
@@ -915,6 +1034,164 @@ mod test {
         );
     }
 
+    #[test]
+    fn featured_word() {
+        let grid = Grid::new("𐑕𐑑𐑨𐑓").unwrap();
+
+        let mut puzzle = Puzzle::new(PuzzleData {
+            grid,
+            words: vec![
+                ("𐑓𐑨𐑑𐑕".to_string(), WordType::Featured),
+                ("𐑕𐑑𐑨𐑓".to_string(), WordType::Normal),
+            ],
+        });
+
+        assert_eq!(puzzle.total_n_words(), 2);
+        assert_eq!(puzzle.total_n_letters(), 8);
+
+        puzzle.score_word("𐑕𐑑𐑨𐑓");
+        assert_eq!(puzzle.pending_word_message(), Some("+4 points!"));
+        assert!(!puzzle.share_text(1).contains("every tile"));
+
+        puzzle.score_word("𐑓𐑨𐑑𐑕");
+        assert_eq!(
+            puzzle.pending_word_message(),
+            Some("Every tile! +4 points!"),
+        );
+        assert!(puzzle.pending_finish());
+
+        puzzle.score_word("𐑓𐑨𐑑𐑕");
+        assert_eq!(puzzle.pending_word_message(), Some("Already found"));
+
+        assert_eq!(
+            &puzzle.share_text(1),
+            "I played WordRoute #1\n\
+             2/2 words\n\
+             🐝 Found the word that uses every tile\n\
+             😎 No hints used\n\
+             🎯 Perfect accuracy",
+        );
+    }
+
+    #[test]
+    fn one_letter_off() {
+        assert!(is_one_letter_off("𐑚𐑤𐑧𐑕", "𐑚𐑤𐑧𐑑"));
+        assert!(is_one_letter_off("𐑚𐑤𐑧𐑕", "𐑚𐑤𐑧𐑕𐑑"));
+        assert!(is_one_letter_off("𐑚𐑤𐑧𐑕𐑑", "𐑚𐑤𐑧𐑕"));
+        assert!(is_one_letter_off("𐑚𐑧𐑕𐑑", "𐑚𐑤𐑧𐑕𐑑"));
+        assert!(is_one_letter_off("abcd", "xbcd"));
+        assert!(is_one_letter_off("abcd", "abxcd"));
+        assert!(!is_one_letter_off("𐑚𐑤𐑧𐑕", "𐑚𐑤𐑧𐑕"));
+        assert!(!is_one_letter_off("abcd", "abdc"));
+        assert!(!is_one_letter_off("abcd", "ab"));
+        assert!(!is_one_letter_off("abcd", "xbcdy"));
+    }
+
+    #[test]
+    fn near_miss() {
+        let mut puzzle = wordy_puzzle();
+
+        assert_eq!(puzzle.near_miss("𐑚𐑤𐑧𐑑"), Some("𐑚𐑤𐑧𐑕"));
+        // Bonus and excluded words aren’t near misses
+        assert!(puzzle.near_miss("𐑧𐑤𐑚𐑑").is_none());
+        assert!(puzzle.near_miss("𐑨𐑚𐑧𐑑").is_none());
+
+        // Disabled by default
+        puzzle.score_word("𐑚𐑤𐑧𐑑");
+        assert_eq!(puzzle.pending_word_message().unwrap(), "Not in list");
+
+        puzzle.set_near_misses_enabled(true);
+
+        puzzle.score_word("𐑚𐑤𐑧𐑑");
+        assert_eq!(
+            puzzle.pending_word_message().unwrap(),
+            "Close! One letter off a word",
+        );
+        assert_eq!(puzzle.misses, 2);
+
+        puzzle.score_word("𐑿𐑿𐑿𐑿");
+        assert_eq!(puzzle.pending_word_message().unwrap(), "Not in list");
+
+        // Words that have already been found don’t count
+        puzzle.score_word("𐑚𐑤𐑧𐑕");
+        puzzle.score_word("𐑚𐑤𐑧𐑕𐑑");
+        puzzle.score_word("𐑚𐑧𐑕𐑑");
+        puzzle.score_word("𐑤𐑧𐑕𐑑");
+        assert!(puzzle.near_miss("𐑚𐑤𐑧𐑑").is_none());
+    }
+
+    #[test]
+    fn large_grid() {
+        let mut grid_string = String::new();
+
+        for _ in 0..8 {
+            grid_string.push_str("𐑿𐑿𐑿𐑿𐑿𐑿𐑿𐑿𐑿\n");
+        }
+
+        grid_string.push_str("𐑿𐑿𐑿𐑿𐑐𐑩𐑑𐑱𐑑");
+
+        let grid = Grid::new(&grid_string).unwrap();
+
+        let long_word = "𐑿".repeat(70);
+
+        let mut puzzle = Puzzle::new(PuzzleData {
+            grid,
+            words: vec![
+                ("𐑐𐑩𐑑𐑱𐑑".to_string(), WordType::Normal),
+                (long_word.clone(), WordType::Normal),
+            ],
+        });
+
+        assert_eq!(puzzle.changed_counts().count(), 81);
+        assert!(puzzle.changed_counts().next().is_none());
+        assert_eq!(
+            &puzzle.changed_word_lists().collect::<Vec<_>>(),
+            &[5, 70],
+        );
+
+        assert_eq!(puzzle.counts().at(4, 8).starts, 1);
+        assert_eq!(puzzle.counts().at(8, 8).visits, 1);
+
+        puzzle.score_word("𐑐𐑩𐑑𐑱𐑑");
+
+        assert_eq!(
+            &puzzle.changed_counts().collect::<Vec<_>>(),
+            &[(4, 8), (5, 8), (6, 8), (7, 8), (8, 8)],
+        );
+        assert_eq!(puzzle.counts().at(8, 8).visits, 0);
+        assert_eq!(&puzzle.changed_word_lists().collect::<Vec<_>>(), &[5]);
+
+        puzzle.score_word(&long_word);
+
+        assert_eq!(puzzle.changed_counts().count(), 70);
+        assert_eq!(&puzzle.changed_word_lists().collect::<Vec<_>>(), &[70]);
+    }
+
+    #[test]
+    fn multi_letter_tiles() {
+        let mut puzzle = Puzzle::new(PuzzleData {
+            grid: Grid::new("[𐑐𐑩] 𐑑 [𐑱𐑑]").unwrap(),
+            words: vec![
+                ("𐑐𐑩𐑑𐑱𐑑".to_string(), WordType::Normal),
+                ("𐑐𐑩𐑑".to_string(), WordType::Normal),
+            ],
+        });
+
+        // The word lists are sorted by the number of letters
+        assert_eq!(&puzzle.word_lists(), &[3, 5]);
+        assert_eq!(puzzle.total_n_letters(), 8);
+
+        assert_eq!(puzzle.counts().at(0, 0).starts, 2);
+        assert_eq!(puzzle.counts().at(1, 0).visits, 2);
+        assert_eq!(puzzle.counts().at(2, 0).visits, 1);
+
+        puzzle.score_word("𐑐𐑩𐑑𐑱𐑑");
+
+        assert_eq!(puzzle.counts().at(0, 0).starts, 1);
+        assert_eq!(puzzle.counts().at(2, 0).visits, 0);
+        assert_eq!(puzzle.changed_n_letters_found(), Some(5));
+    }
+
     #[test]
     fn counts() {
         let puzzle = wordy_puzzle();
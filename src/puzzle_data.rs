@@ -24,6 +24,26 @@ pub enum WordType {
     Normal,
     Bonus,
     Excluded,
+    // A normal word whose route uses every tile of the grid
+    Featured,
+}
+
+impl WordType {
+    // Featured words are also normal words, so they need to be found
+    // to finish the puzzle
+    pub fn is_normal(self) -> bool {
+        matches!(self, WordType::Normal | WordType::Featured)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn name(self) -> &'static str {
+        match self {
+            WordType::Normal => "normal",
+            WordType::Bonus => "bonus",
+            WordType::Excluded => "excluded",
+            WordType::Featured => "featured",
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -32,23 +52,23 @@ pub struct PuzzleData {
     pub words: Vec<(String, WordType)>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum Error {
-    GridError(grid::Error),
+    Grid(grid::Error),
     EmptyWord,
     InvalidWordType,
 }
 
 impl From<grid::Error> for Error {
     fn from(e: grid::Error) -> Error {
-        Error::GridError(e)
+        Error::Grid(e)
     }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Error::GridError(e) => e.fmt(f),
+            Error::Grid(e) => e.fmt(f),
             Error::InvalidWordType => write!(f, "invalid word type"),
             Error::EmptyWord => write!(f, "empty word"),
         }
@@ -91,6 +111,7 @@ impl fmt::Display for PuzzleData {
                 WordType::Normal => (),
                 WordType::Bonus => write!(f, ":b")?,
                 WordType::Excluded => write!(f, ":x")?,
+                WordType::Featured => write!(f, ":f")?,
             }
         }
 
@@ -105,6 +126,7 @@ fn parse_word(s: &str) -> Result<(String, WordType), Error> {
             let word_type = match word_type {
                 "b" => WordType::Bonus,
                 "x" => WordType::Excluded,
+                "f" => WordType::Featured,
                 _ => return Err(Error::InvalidWordType),
             };
 
@@ -129,13 +151,15 @@ mod test {
 
         assert_eq!(puzzle.grid.width(), 1);
         assert_eq!(puzzle.grid.height(), 1);
-        assert_eq!(puzzle.grid.at(0, 0), '𐑖');
+        assert_eq!(puzzle.grid.at(0, 0), "𐑖");
         assert!(&puzzle.words.is_empty());
 
-        let puzzle = "AB:CB,𐑓j𐑑,𐑯𐑪𐑜𐑦𐑯:b,KjV:x".parse::<PuzzleData>().unwrap();
+        let puzzle = "AB:CB,𐑓j𐑑,𐑯𐑪𐑜𐑦𐑯:b,KjV:x,ABCB:f"
+            .parse::<PuzzleData>()
+            .unwrap();
         assert_eq!(puzzle.grid.width(), 2);
         assert_eq!(puzzle.grid.height(), 2);
-        assert_eq!(puzzle.grid.at(1, 1), '𐑑');
+        assert_eq!(puzzle.grid.at(1, 1), "𐑑");
         assert_eq!(
             &puzzle.words.iter()
                 .map(|(w, t)| (w.as_str(), *t))
@@ -144,10 +168,27 @@ mod test {
                 ("𐑓𐑳𐑑", WordType::Normal),
                 ("𐑯𐑪𐑜𐑦𐑯", WordType::Bonus),
                 ("𐑚𐑳𐑥", WordType::Excluded),
+                ("𐑐𐑑𐑒𐑑", WordType::Featured),
             ],
         );
     }
 
+    #[test]
+    fn word_type_names() {
+        assert_eq!(WordType::Normal.name(), "normal");
+        assert_eq!(WordType::Bonus.name(), "bonus");
+        assert_eq!(WordType::Excluded.name(), "excluded");
+        assert_eq!(WordType::Featured.name(), "featured");
+    }
+
+    #[test]
+    fn normal_word_types() {
+        assert!(WordType::Normal.is_normal());
+        assert!(WordType::Featured.is_normal());
+        assert!(!WordType::Bonus.is_normal());
+        assert!(!WordType::Excluded.is_normal());
+    }
+
     #[test]
     fn parse_error() {
         assert_eq!(
@@ -172,8 +213,15 @@ mod test {
     fn display() {
         assert_eq!(&"a".parse::<PuzzleData>().unwrap().to_string(), "a");
         assert_eq!(
-            &"a,head,noggin:b,bum:x".parse::<PuzzleData>().unwrap().to_string(),
-            "a,head,noggin:b,bum:x",
+            &"a,head,noggin:b,bum:x,a:f"
+                .parse::<PuzzleData>()
+                .unwrap()
+                .to_string(),
+            "a,head,noggin:b,bum:x,a:f",
+        );
+        assert_eq!(
+            &"#ab:cd,bad".parse::<PuzzleData>().unwrap().to_string(),
+            "#ab:cd,bad",
         );
     }
 }
@@ -0,0 +1,185 @@
+// Wordroute – A word game
+// Copyright (C) 2024  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Describes a built puzzle as JSON so that it can be used from
+// scripts
+
+use super::puzzle_data::{PuzzleData, WordType};
+use super::counts::GridCounts;
+use super::directions::Shape;
+use super::word_finder;
+use super::build;
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct Word<'a> {
+    word: &'a str,
+    #[serde(rename = "type")]
+    word_type: &'static str,
+    length: usize,
+    // The first route that the word finder finds, which is the one
+    // used for the counts
+    start: (u32, u32),
+    route: Vec<u8>,
+}
+
+#[derive(Serialize)]
+struct Tile {
+    starts: u8,
+    visits: u8,
+}
+
+#[derive(Serialize)]
+struct Stats {
+    words: usize,
+    featured_words: usize,
+    bonus_words: usize,
+    excluded_words: usize,
+    longest_word: usize,
+    unused_tiles: usize,
+}
+
+#[derive(Serialize)]
+struct Puzzle<'a> {
+    shape: &'static str,
+    grid: Vec<Vec<&'a str>>,
+    code: String,
+    words: Vec<Word<'a>>,
+    counts: Vec<Vec<Tile>>,
+    stats: Stats,
+}
+
+// The counts should be the ones for the normal words
+pub fn to_json(puzzle_data: &PuzzleData, counts: &GridCounts) -> String {
+    let grid = &puzzle_data.grid;
+    let mut finder = word_finder::Finder::new();
+
+    let words = puzzle_data.words.iter().map(|&(ref word, word_type)| {
+        let mut route = Vec::new();
+        let start = finder.find(grid, word, &mut route).unwrap();
+
+        Word {
+            word,
+            word_type: word_type.name(),
+            length: word.chars().count(),
+            start,
+            route,
+        }
+    }).collect::<Vec<_>>();
+
+    let count_type = |word_type: WordType| {
+        puzzle_data.words.iter().filter(|&&(_, t)| t == word_type).count()
+    };
+
+    let stats = Stats {
+        words: puzzle_data.words.iter()
+            .filter(|&&(_, t)| t.is_normal())
+            .count(),
+        featured_words: count_type(WordType::Featured),
+        bonus_words: count_type(WordType::Bonus),
+        excluded_words: count_type(WordType::Excluded),
+        longest_word: puzzle_data.words.iter()
+            .filter(|&&(_, t)| t.is_normal())
+            .map(|(word, _)| word.chars().count())
+            .max()
+            .unwrap_or(0),
+        unused_tiles: build::unused_tiles(grid, counts).len(),
+    };
+
+    let puzzle = Puzzle {
+        shape: match grid.shape() {
+            Shape::Hexagon => "hexagon",
+            Shape::Square => "square",
+        },
+        grid: (0..grid.height()).map(|y| {
+            (0..grid.width()).map(|x| grid.at(x, y)).collect()
+        }).collect(),
+        code: puzzle_data.to_string(),
+        counts: (0..grid.height()).map(|y| {
+            (0..grid.width()).map(|x| {
+                let tile = counts.at(x, y);
+                Tile { starts: tile.starts, visits: tile.visits }
+            }).collect()
+        }).collect(),
+        words,
+        stats,
+    };
+
+    serde_json::to_string(&puzzle).unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn puzzle() {
+        let puzzle_data = "#𐑒𐑨𐑚:𐑿𐑑.,𐑒𐑨𐑚,𐑚𐑨𐑑:b"
+            .parse::<PuzzleData>()
+            .unwrap();
+        let counts = build::count_visits(&puzzle_data.grid, ["𐑒𐑨𐑚"]);
+
+        let value = serde_json::from_str::<Value>(
+            &to_json(&puzzle_data, &counts)
+        ).unwrap();
+
+        assert_eq!(
+            value,
+            json!({
+                "shape": "square",
+                "grid": [["𐑒", "𐑨", "𐑚"], ["𐑿", "𐑑", "."]],
+                "code": "#CYK:vB.,CYK,KYB:b",
+                "words": [
+                    {
+                        "word": "𐑒𐑨𐑚",
+                        "type": "normal",
+                        "length": 3,
+                        "start": [0, 0],
+                        "route": [4, 4],
+                    },
+                    {
+                        "word": "𐑚𐑨𐑑",
+                        "type": "bonus",
+                        "length": 3,
+                        "start": [2, 0],
+                        "route": [3, 6],
+                    },
+                ],
+                "counts": [
+                    [
+                        { "starts": 1, "visits": 1 },
+                        { "starts": 0, "visits": 1 },
+                        { "starts": 0, "visits": 1 },
+                    ],
+                    [
+                        { "starts": 0, "visits": 0 },
+                        { "starts": 0, "visits": 0 },
+                        { "starts": 0, "visits": 0 },
+                    ],
+                ],
+                "stats": {
+                    "words": 1,
+                    "featured_words": 0,
+                    "bonus_words": 1,
+                    "excluded_words": 0,
+                    "longest_word": 3,
+                    "unused_tiles": 2,
+                },
+            }),
+        );
+    }
+}
@@ -0,0 +1,83 @@
+// Wordroute – A word game
+// Copyright (C) 2024  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Puts the terminal into a mode where each key press can be read as
+// soon as it is typed, without waiting for Enter. This uses the stty
+// command so that it doesn’t need any extra dependencies. The old
+// settings are restored when the RawTerminal is dropped.
+
+use std::process::{Command, Stdio};
+use std::io::Read;
+
+pub struct RawTerminal {
+    saved_settings: String,
+}
+
+// The key that Ctrl+C sends when signals are turned off
+pub const INTERRUPT: u8 = 0x03;
+// The key that Ctrl+D sends when the terminal isn’t in line mode
+pub const END_OF_FILE: u8 = 0x04;
+
+fn stty(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("stty")
+        .args(args)
+        // stty changes the terminal that is its standard input
+        .stdin(Stdio::inherit())
+        .output()
+        .map_err(|e| format!("stty: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(format!(
+            "stty: {}",
+            String::from_utf8_lossy(&output.stderr).trim(),
+        ))
+    }
+}
+
+impl RawTerminal {
+    // Fails if the standard input isn’t a terminal
+    pub fn new() -> Result<RawTerminal, String> {
+        let saved_settings = stty(&["-g"])?;
+
+        // Signals are turned off so that Ctrl+C can be read as a key
+        // and the settings can still be restored afterwards
+        stty(&["-icanon", "-echo", "-isig", "min", "1", "time", "0"])?;
+
+        Ok(RawTerminal { saved_settings })
+    }
+
+    // Returns None at the end of the input
+    pub fn read_key<R: Read>(
+        &self,
+        input: &mut R,
+    ) -> Result<Option<u8>, String> {
+        let mut key = [0u8];
+
+        match input.read(&mut key) {
+            Ok(0) => Ok(None),
+            Ok(_) => Ok(Some(key[0])),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.saved_settings]);
+    }
+}
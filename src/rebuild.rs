@@ -0,0 +1,191 @@
+// Wordroute – A word game
+// Copyright (C) 2024  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Compares the words of a published puzzle with the words that a new
+// search of its grid finds
+
+use super::puzzle_data::WordType;
+use std::collections::HashSet;
+
+pub struct Changes {
+    // The new words along with the type that they would be given
+    pub added: Vec<(String, WordType)>,
+    pub removed: Vec<(String, WordType)>,
+    // Normal words that are kept but should now be featured. Changing
+    // the type doesn’t move any words so it is always safe.
+    pub featured: Vec<String>,
+}
+
+impl Changes {
+    pub fn new<F>(
+        words: &[(String, WordType)],
+        found: &HashSet<String>,
+        word_type: F,
+    ) -> Changes
+        where F: Fn(&str) -> WordType
+    {
+        let old_words = words.iter()
+            .map(|(word, _)| word.as_str())
+            .collect::<HashSet<_>>();
+
+        let mut added = found.iter()
+            .filter(|word| !old_words.contains(word.as_str()))
+            .map(|word| (word.clone(), word_type(word)))
+            .collect::<Vec<_>>();
+
+        added.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+        let removed = words.iter()
+            .filter(|(word, _)| !found.contains(word))
+            .cloned()
+            .collect();
+
+        let featured = words.iter()
+            .filter(|(word, old_type)| {
+                *old_type == WordType::Normal &&
+                    found.contains(word) &&
+                    word_type(word) == WordType::Featured
+            })
+            .map(|(word, _)| word.clone())
+            .collect();
+
+        Changes { added, removed, featured }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() &&
+            self.removed.is_empty() &&
+            self.featured.is_empty()
+    }
+
+    // Applies the changes to the word list. Apart from the featured
+    // words, the words that are kept keep the type that they already
+    // had.
+    pub fn apply(
+        &self,
+        words: &[(String, WordType)],
+    ) -> Vec<(String, WordType)> {
+        let removed = self.removed.iter()
+            .map(|(word, _)| word.as_str())
+            .collect::<HashSet<_>>();
+
+        let mut result = words.iter()
+            .filter(|(word, _)| !removed.contains(word.as_str()))
+            .chain(self.added.iter())
+            .map(|(word, word_type)| {
+                if self.featured.contains(word) {
+                    (word.clone(), WordType::Featured)
+                } else {
+                    (word.clone(), *word_type)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // The save states refer to the words by their position in the
+        // sorted list
+        result.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_words() -> Vec<(String, WordType)> {
+        vec![
+            ("𐑒𐑨𐑚".to_string(), WordType::Normal),
+            ("𐑕𐑑𐑨𐑓".to_string(), WordType::Excluded),
+            ("𐑚𐑨𐑑".to_string(), WordType::Bonus),
+        ]
+    }
+
+    #[test]
+    fn changes() {
+        let words = make_words();
+        let found = ["𐑒𐑨𐑚", "𐑕𐑑𐑨𐑓", "𐑑𐑨𐑚", "𐑑𐑨𐑒"]
+            .into_iter()
+            .map(str::to_string)
+            .collect::<HashSet<_>>();
+
+        let changes = Changes::new(&words, &found, |word| {
+            if word == "𐑑𐑨𐑒" {
+                WordType::Bonus
+            } else {
+                WordType::Normal
+            }
+        });
+
+        assert!(!changes.is_empty());
+        assert_eq!(
+            changes.added,
+            [
+                ("𐑑𐑨𐑒".to_string(), WordType::Bonus),
+                ("𐑑𐑨𐑚".to_string(), WordType::Normal),
+            ],
+        );
+        assert_eq!(changes.removed, [("𐑚𐑨𐑑".to_string(), WordType::Bonus)]);
+
+        assert_eq!(
+            changes.apply(&words),
+            [
+                ("𐑑𐑨𐑒".to_string(), WordType::Bonus),
+                ("𐑑𐑨𐑚".to_string(), WordType::Normal),
+                ("𐑒𐑨𐑚".to_string(), WordType::Normal),
+                ("𐑕𐑑𐑨𐑓".to_string(), WordType::Excluded),
+            ],
+        );
+    }
+
+    #[test]
+    fn featured() {
+        let words = make_words();
+        let found = words.iter()
+            .map(|(word, _)| word.clone())
+            .collect::<HashSet<_>>();
+
+        // Only the normal word becomes featured
+        let changes = Changes::new(&words, &found, |_| WordType::Featured);
+
+        assert!(!changes.is_empty());
+        assert!(changes.added.is_empty());
+        assert!(changes.removed.is_empty());
+        assert_eq!(changes.featured, ["𐑒𐑨𐑚"]);
+
+        assert_eq!(
+            changes.apply(&words),
+            [
+                ("𐑒𐑨𐑚".to_string(), WordType::Featured),
+                ("𐑕𐑑𐑨𐑓".to_string(), WordType::Excluded),
+                ("𐑚𐑨𐑑".to_string(), WordType::Bonus),
+            ],
+        );
+    }
+
+    #[test]
+    fn no_changes() {
+        let words = make_words();
+        let found = words.iter()
+            .map(|(word, _)| word.clone())
+            .collect::<HashSet<_>>();
+
+        let changes = Changes::new(&words, &found, |_| WordType::Normal);
+
+        assert!(changes.is_empty());
+        assert_eq!(changes.apply(&words), words);
+    }
+}
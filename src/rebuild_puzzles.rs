@@ -0,0 +1,240 @@
+// Wordroute – A word game
+// Copyright (C) 2024  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+#[allow(dead_code)]
+mod grid;
+#[allow(dead_code)]
+mod build;
+#[allow(dead_code)]
+mod dictionary;
+mod layered_dictionary;
+mod directions;
+#[allow(dead_code)]
+mod word_finder;
+mod counts;
+#[allow(dead_code)]
+mod puzzle_data;
+#[allow(dead_code)]
+mod shavicode;
+mod load;
+mod rebuild;
+
+use std::{fs, process::ExitCode, ffi::OsString};
+use std::num::NonZeroUsize;
+use clap::Parser;
+use puzzle_data::{PuzzleData, WordType};
+use rebuild::Changes;
+
+#[derive(Parser)]
+#[command(name = "RebuildPuzzles")]
+struct Cli {
+    #[arg(value_name = "PUZZLES", default_value = "puzzles.txt")]
+    puzzles: OsString,
+    #[arg(short, long, value_name = "FILE", required = true)]
+    dictionary: Vec<OsString>,
+    /// Text file with a list of extra words to add to the dictionary
+    #[arg(short, long, value_name = "FILE")]
+    add_words: Vec<OsString>,
+    /// Text file with a list of words to remove from the dictionary
+    #[arg(short, long, value_name = "FILE")]
+    remove_words: Vec<OsString>,
+    #[arg(short, long, value_name = "FILE")]
+    bonus_words: Vec<OsString>,
+    #[arg(short = 'x', long, value_name = "FILE")]
+    excluded_words: Vec<OsString>,
+    /// Make any new word whose value in the dictionary is less than
+    /// VALUE into a bonus word
+    #[arg(long, value_name = "VALUE")]
+    bonus_below: Option<u32>,
+    #[arg(short, long, value_name = "LENGTH", default_value_t = 4)]
+    minimum_length: usize,
+    /// Rewrite the puzzle on line LINE with the new words. If any
+    /// words are added or removed then this changes the position of
+    /// the words so it will break the saved progress of anyone who
+    /// has played the puzzle.
+    #[arg(long, value_name = "LINE")]
+    update: Vec<usize>,
+    /// Number of threads to use to search for words. Defaults to
+    /// the number of available CPUs
+    #[arg(short, long, value_name = "N")]
+    jobs: Option<NonZeroUsize>,
+}
+
+fn print_words(prefix: char, words: &[(String, WordType)]) {
+    for (word, word_type) in words.iter() {
+        match word_type {
+            WordType::Normal => println!("  {} {}", prefix, word),
+            _ => println!("  {} {} ({})", prefix, word, word_type.name()),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let filename = cli.puzzles.to_string_lossy();
+
+    let dictionary = match load::load_dictionaries(
+        &cli.dictionary,
+        &cli.add_words,
+        &cli.remove_words,
+    ) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        },
+    };
+
+    let bonus_words = match load::read_word_list(cli.bonus_words.iter()) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let excluded_words = match load::read_word_list(cli.excluded_words.iter()) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let data = match fs::read_to_string(&cli.puzzles) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("{}: {}", filename, e);
+            return ExitCode::FAILURE;
+        },
+    };
+
+    let lines = data.lines().collect::<Vec<_>>();
+
+    if let Some(&line_num) = cli.update.iter()
+        .find(|&&line_num| line_num < 1 || line_num > lines.len())
+    {
+        eprintln!(
+            "{}: there is no line {} to update",
+            filename,
+            line_num,
+        );
+        return ExitCode::FAILURE;
+    }
+
+    let mut puzzles = Vec::with_capacity(lines.len());
+
+    for (line_num, line) in lines.iter().enumerate() {
+        match line.parse::<PuzzleData>() {
+            Ok(p) => puzzles.push(p),
+            Err(e) => {
+                eprintln!("{}:{}: {}", filename, line_num + 1, e);
+                return ExitCode::FAILURE;
+            },
+        }
+    }
+
+    let n_threads = cli.jobs.unwrap_or_else(|| {
+        std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN)
+    });
+
+    let grid_words = build::search_grids(
+        &puzzles.iter().map(|p| &p.grid).collect::<Vec<_>>(),
+        &dictionary,
+        cli.minimum_length,
+        n_threads,
+    );
+
+    let word_type = |word: &str| {
+        if excluded_words.contains(word) {
+            WordType::Excluded
+        } else if bonus_words.contains(word) ||
+            cli.bonus_below.is_some_and(|threshold| {
+                dictionary.value(word).is_some_and(|value| value < threshold)
+            })
+        {
+            WordType::Bonus
+        } else {
+            WordType::Normal
+        }
+    };
+
+    let mut new_lines = Vec::with_capacity(lines.len());
+    let mut n_changed = 0;
+    let mut n_updated = 0;
+
+    for ((puzzle_data, found), line_num) in puzzles.into_iter()
+        .zip(grid_words)
+        .zip(1..)
+    {
+        let grid = &puzzle_data.grid;
+
+        let changes = Changes::new(&puzzle_data.words, &found, |word| {
+            match word_type(word) {
+                WordType::Normal if build::uses_all_tiles(grid, word) => {
+                    WordType::Featured
+                },
+                word_type => word_type,
+            }
+        });
+
+        if changes.is_empty() {
+            new_lines.push(lines[line_num - 1].to_string());
+            continue;
+        }
+
+        n_changed += 1;
+
+        println!("{}:{}:", filename, line_num);
+        print_words('+', &changes.added);
+        print_words('-', &changes.removed);
+
+        for word in changes.featured.iter() {
+            println!("  * {} (featured)", word);
+        }
+
+        if cli.update.contains(&line_num) {
+            let words = changes.apply(&puzzle_data.words);
+            new_lines.push(
+                PuzzleData { grid: puzzle_data.grid, words }.to_string()
+            );
+            n_updated += 1;
+        } else {
+            new_lines.push(lines[line_num - 1].to_string());
+        }
+    }
+
+    if n_updated > 0 {
+        let mut contents = new_lines.join("\n");
+        contents.push('\n');
+
+        if let Err(e) = fs::write(&cli.puzzles, contents) {
+            eprintln!("{}: {}", filename, e);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    if n_changed > n_updated {
+        eprintln!(
+            "{} of {} puzzles would change. Use --update LINE to rewrite \
+             a puzzle.",
+            n_changed - n_updated,
+            lines.len(),
+        );
+    }
+
+    ExitCode::SUCCESS
+}
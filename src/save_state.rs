@@ -53,15 +53,15 @@ impl SaveState {
     }
 
     pub fn misses(&self) -> u32 {
-        return self.misses;
+        self.misses
     }
 
     pub fn hints_used(&self) -> bool {
-        return self.hints_used;
+        self.hints_used
     }
 
-    pub fn found_words(&self) -> FoundWords {
-        return FoundWords::new(&self.found_words)
+    pub fn found_words(&self) -> FoundWords<'_> {
+        FoundWords::new(&self.found_words)
     }
 }
 
@@ -72,8 +72,8 @@ pub struct FoundWords<'a> {
 }
 
 impl<'a> FoundWords<'a> {
-    fn new(slice: &'a [u32]) -> FoundWords {
-        let &bits = slice.get(0).unwrap_or(&0);
+    fn new(slice: &'a [u32]) -> FoundWords<'a> {
+        let &bits = slice.first().unwrap_or(&0);
 
         FoundWords {
             bits,
@@ -104,7 +104,7 @@ impl<'a> Iterator for FoundWords<'a> {
     fn count(self) -> usize {
         self.slice.get(self.pos + 1..)
             .map(|slice| {
-                slice.into_iter().map(|bits| {
+                slice.iter().map(|bits| {
                     bits.count_ones() as usize
                 }).sum()
             })
@@ -188,7 +188,7 @@ impl FromStr for SaveState {
             _ => return Err(Error::InvalidHintsUsed),
         };
 
-        let Some(found_words) = parts.next().and_then(|p| parse_found_words(p))
+        let Some(found_words) = parts.next().and_then(parse_found_words)
         else {
             return Err(Error::InvalidFoundWords);
         };
@@ -364,7 +364,7 @@ mod test {
 
         let puzzles = parse_multiple("23:0.0.0,5:1.1.1,6:2.0.2").unwrap();
         assert_eq!(puzzles.len(), 3);
-        let mut keys = puzzles.keys().map(|&k| k).collect::<Vec<_>>();
+        let mut keys = puzzles.keys().copied().collect::<Vec<_>>();
         keys.sort_unstable();
         assert_eq!(&keys, &[5, 6, 23]);
         assert_eq!(&puzzles[&6].to_string(), "2.0.2");
@@ -18,11 +18,22 @@ const FIRST_LETTER_SHAVIAN: u32 = '𐑐' as u32;
 const LAST_LETTER_SHAVIAN: u32 = '𐑿' as u32;
 const N_LETTERS: u32 = LAST_LETTER_SHAVIAN - FIRST_LETTER_SHAVIAN + 1;
 
+// Whether the character is one of the letters of the Shavian alphabet
+pub fn is_letter(ch: char) -> bool {
+    (FIRST_LETTER_SHAVIAN..=LAST_LETTER_SHAVIAN).contains(&(ch as u32))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+// Iterates all of the letters of the Shavian alphabet
+pub fn all_letters() -> impl Iterator<Item = char> {
+    (FIRST_LETTER_SHAVIAN..=LAST_LETTER_SHAVIAN).filter_map(char::from_u32)
+}
+
 pub fn decode_char(ch: char) -> char {
-    if ch >= 'A' && ch <= 'Z' {
+    if ch.is_ascii_uppercase() {
         char::from_u32(ch as u32 - 'A' as u32 + FIRST_LETTER_SHAVIAN)
             .unwrap()
-    } else if ch >= 'a' && ch as u32 <= 'a' as u32 + N_LETTERS - 26 - 1 {
+    } else if ch >= 'a' && (ch as u32) < 'a' as u32 + N_LETTERS - 26 {
         char::from_u32(ch as u32 - 'a' as u32 + FIRST_LETTER_SHAVIAN + 26)
             .unwrap()
     } else {
@@ -76,6 +87,20 @@ mod test {
         );
     }
 
+    #[test]
+    fn letters() {
+        assert!(is_letter('𐑐'));
+        assert!(is_letter('𐑿'));
+        assert!(!is_letter('\u{1044f}'));
+        assert!(!is_letter('\u{10480}'));
+        assert!(!is_letter('a'));
+
+        assert_eq!(all_letters().count(), 48);
+        assert!(all_letters().all(is_letter));
+        assert_eq!(all_letters().next(), Some('𐑐'));
+        assert_eq!(all_letters().last(), Some('𐑿'));
+    }
+
     #[test]
     fn encode_outside_range() {
         assert_eq!(encode_char('\u{1044f}'), '\u{1044f}');
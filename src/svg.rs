@@ -0,0 +1,248 @@
+// Wordroute – A word game
+// Copyright (C) 2024  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Draws a grid as an SVG image in the same style as the game
+
+use super::grid::Grid;
+use super::grid_math::Geometry;
+use super::counts::GridCounts;
+use std::fmt::Write;
+
+// Same width as the game uses for the grid
+const VIEWPORT_WIDTH: f32 = 100.0;
+
+pub struct Route<'a> {
+    pub start: (u32, u32),
+    pub steps: &'a [u8],
+}
+
+// Appends the text with the characters that are special in XML
+// replaced by entities
+pub fn write_escaped(svg: &mut String, text: &str) {
+    for ch in text.chars() {
+        match ch {
+            '&' => svg.push_str("&amp;"),
+            '<' => svg.push_str("&lt;"),
+            '>' => svg.push_str("&gt;"),
+            '"' => svg.push_str("&quot;"),
+            ch => svg.push(ch),
+        }
+    }
+}
+
+fn write_text(
+    svg: &mut String,
+    y: f32,
+    font_size: f32,
+    fill: &str,
+    text: &str,
+) {
+    write!(
+        svg,
+        "    <text text-anchor=\"middle\" font-family=\"sans-serif\" \
+         x=\"0\" y=\"{}\" font-size=\"{}\" fill=\"{}\">",
+        y,
+        font_size,
+        fill,
+    ).unwrap();
+    write_escaped(svg, text);
+    svg.push_str("</text>\n");
+}
+
+fn write_route(
+    svg: &mut String,
+    grid: &Grid,
+    geometry: &Geometry,
+    route: &Route,
+) {
+    let (start_x, start_y) = route.start;
+    let (cx, cy) = geometry.convert_coords(start_x, start_y);
+
+    svg.push_str("  <g fill=\"none\" stroke=\"red\" opacity=\"0.7\" \
+                  stroke-linecap=\"round\" stroke-linejoin=\"round\">\n");
+
+    writeln!(
+        svg,
+        "    <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"red\" \
+         stroke=\"none\" />",
+        cx,
+        cy,
+        geometry.radius * 0.4,
+    ).unwrap();
+
+    if !route.steps.is_empty() {
+        let (mut x, mut y) = (start_x, start_y);
+        let mut path_d = format!("M {},{}", cx, cy);
+        let topology = grid.topology();
+
+        for &dir in route.steps.iter() {
+            (x, y) = topology.step(x, y, dir);
+            let (x, y) = geometry.convert_coords(x, y);
+            write!(&mut path_d, " L {},{}", x, y).unwrap();
+        }
+
+        writeln!(
+            svg,
+            "    <path d=\"{}\" stroke-width=\"{}\" />",
+            path_d,
+            geometry.radius * 0.3,
+        ).unwrap();
+    }
+
+    svg.push_str("  </g>\n");
+}
+
+// Writes a group for each tile with its letter and optionally the
+// counts
+pub fn write_tiles(
+    svg: &mut String,
+    grid: &Grid,
+    geometry: &Geometry,
+    counts: Option<&GridCounts>,
+) {
+    let tile_path = geometry.tile_path();
+
+    let font_size = geometry.radius;
+    let text_y_pos = geometry.radius * 0.25;
+    let counts_font_size = geometry.radius * 0.3;
+
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            let letter = grid.at(x, y);
+
+            if letter == "." {
+                continue;
+            }
+
+            let (x_center, y_center) = geometry.convert_coords(x, y);
+
+            writeln!(
+                svg,
+                "  <g transform=\"translate({}, {})\">\n    \
+                 <path d=\"{}\" fill=\"#eee\" stroke=\"#aaa\" \
+                 stroke-width=\"0.02em\" />",
+                x_center,
+                y_center,
+                tile_path,
+            ).unwrap();
+
+            // Shrink the text for tiles with multiple letters
+            let n_letters = letter.chars().count();
+            let font_size = if n_letters > 1 {
+                font_size * 1.5 / n_letters as f32
+            } else {
+                font_size
+            };
+
+            write_text(svg, text_y_pos, font_size, "#000", letter);
+
+            if let Some(counts) = counts {
+                let counts = counts.at(x, y);
+
+                write_text(
+                    svg,
+                    -geometry.radius * 0.6,
+                    counts_font_size,
+                    "#811",
+                    &counts.starts.to_string(),
+                );
+                write_text(
+                    svg,
+                    geometry.radius * 0.8,
+                    counts_font_size,
+                    "#333",
+                    &counts.visits.to_string(),
+                );
+            }
+
+            svg.push_str("  </g>\n");
+        }
+    }
+}
+
+// The counts are drawn on the tiles if they are given, and the route
+// is drawn on top of everything
+pub fn grid_to_svg(
+    grid: &Grid,
+    counts: Option<&GridCounts>,
+    route: Option<&Route>,
+) -> String {
+    let geometry = Geometry::new(grid, VIEWPORT_WIDTH);
+
+    let mut svg = String::new();
+
+    writeln!(
+        svg,
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n\
+         <svg version=\"1.1\" viewBox=\"0 0 {} {}\" \
+         xmlns=\"http://www.w3.org/2000/svg\">",
+        geometry.width,
+        geometry.height,
+    ).unwrap();
+
+    write_tiles(&mut svg, grid, &geometry, counts);
+
+    if let Some(route) = route {
+        write_route(&mut svg, grid, &geometry, route);
+    }
+
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn letters() {
+        let grid = Grid::new("#𐑒𐑨𐑚:𐑿𐑑.").unwrap();
+        let svg = grid_to_svg(&grid, None, None);
+
+        assert!(svg.starts_with("<?xml"));
+        assert!(svg.contains("viewBox=\"0 0 100 66.6"));
+        assert!(svg.ends_with("</svg>\n"));
+        // The blank tile isn’t drawn
+        assert_eq!(svg.matches("<g ").count(), 5);
+        assert_eq!(svg.matches("<text ").count(), 5);
+        assert!(svg.contains(">𐑿</text>"));
+        assert!(!svg.contains("<circle"));
+    }
+
+    #[test]
+    fn counts_and_route() {
+        let grid = Grid::new("#𐑒𐑨𐑚:𐑿𐑑.").unwrap();
+        let mut counts = GridCounts::new(grid.width(), grid.height());
+        counts.at_mut(0, 0).starts = 1;
+        counts.at_mut(1, 0).visits = 2;
+        let route = Route { start: (2, 0), steps: &[3, 6] };
+        let svg = grid_to_svg(&grid, Some(&counts), Some(&route));
+
+        assert_eq!(svg.matches("<text ").count(), 15);
+        assert!(svg.contains("fill=\"#811\">1</text>"));
+        assert!(svg.contains("fill=\"#333\">2</text>"));
+        assert!(svg.contains("<circle cx=\"83.3"));
+        assert!(svg.contains("<path d=\"M 83.3"));
+    }
+
+    #[test]
+    fn escape() {
+        let mut svg = String::new();
+        write_escaped(&mut svg, "<a href=\"x\">&</a>");
+        assert_eq!(svg, "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+    }
+}
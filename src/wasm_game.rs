@@ -18,12 +18,10 @@ use wasm_bindgen::prelude::*;
 use web_sys::console;
 use super::grid_math::Geometry;
 use super::word_finder;
-use super::directions;
 use super::puzzle::{Puzzle, N_HINT_LEVELS};
-use super::puzzle_data::{PuzzleData, WordType};
+use super::puzzle_data::PuzzleData;
 use super::save_state::{self, SaveState};
 use std::fmt::Write;
-use std::f32::consts::PI;
 use std::collections::{hash_map, HashMap};
 
 const SAVE_STATE_KEY: &'static str = "wordroute-save-states";
@@ -31,6 +29,8 @@ const SVG_NAMESPACE: &'static str = "http://www.w3.org/2000/svg";
 const ROUTE_ID: &'static str = "route-line";
 const SORT_HINT_CHECKBOX_ID: &'static str = "sort-hint-checkbox";
 const LETTERS_HINT_CHECKBOX_ID: &'static str = "letters-hint-checkbox";
+const NEAR_MISSES_HINT_CHECKBOX_ID: &'static str =
+    "near-misses-hint-checkbox";
 const SHARE_TEXT_COPIED_ID: &'static str = "share-text-copied";
 const SHARE_TEXT_ID: &'static str = "share-text";
 
@@ -510,7 +510,11 @@ impl Wordroute {
             }
         );
 
-        for id in [SORT_HINT_CHECKBOX_ID, LETTERS_HINT_CHECKBOX_ID].iter() {
+        for id in [
+            SORT_HINT_CHECKBOX_ID,
+            LETTERS_HINT_CHECKBOX_ID,
+            NEAR_MISSES_HINT_CHECKBOX_ID,
+        ].iter() {
             if let Some(element) = self.context.document.get_element_by_id(id) {
                 let _ = element.add_event_listener_with_callback(
                     "change",
@@ -713,7 +717,7 @@ impl Wordroute {
     }
 
     fn create_letters(&mut self) -> Result<(), String> {
-        let hexagon_path = hexagon_path(self.geometry.radius);
+        let tile_path = self.geometry.tile_path();
 
         let font_size = self.geometry.radius;
         let text_y_pos = self.geometry.radius * 0.25;
@@ -726,7 +730,7 @@ impl Wordroute {
         {
             let letter = self.puzzle.grid().at(x, y);
 
-            if letter == '.' {
+            if letter == "." {
                 self.letters.push(None);
                 continue;
             }
@@ -743,13 +747,21 @@ impl Wordroute {
             g.set_id(&format!("letter-{}-{}", x, y));
 
             let path = self.create_svg_element("path")?;
-            let _ = path.set_attribute("d", &hexagon_path);
+            let _ = path.set_attribute("d", &tile_path);
 
             let _ = g.append_with_node_1(&path);
 
+            // Shrink the text for tiles with multiple letters
+            let n_letters = letter.chars().count();
+            let font_size = if n_letters > 1 {
+                font_size * 1.5 / n_letters as f32
+            } else {
+                font_size
+            };
+
             let text = self.create_letter_text(text_y_pos, font_size)?;
 
-            set_element_text(&text, &self.puzzle.grid().at(x, y).to_string());
+            set_element_text(&text, letter);
 
             let _ = g.append_with_node_1(&text);
 
@@ -838,7 +850,7 @@ impl Wordroute {
         let mut found_words = Vec::new();
 
         for (key, word) in self.puzzle.words() {
-            if word.length != length || word.word_type != WordType::Normal {
+            if word.length != length || !word.word_type.is_normal() {
                 continue;
             }
 
@@ -964,9 +976,10 @@ impl Wordroute {
             if !self.route_steps.is_empty() {
                 let (mut x, mut y) = (start_x, start_y);
                 let mut path_d = format!("M {},{}", cx, cy);
+                let topology = self.puzzle.grid().topology();
 
                 for &dir in self.route_steps.iter() {
-                    (x, y) = directions::step(x, y, dir);
+                    (x, y) = topology.step(x, y, dir);
                     let (x, y) = self.geometry.convert_coords(x, y);
                     write!(&mut path_d, "L {},{}", x, y).unwrap();
                 }
@@ -996,13 +1009,24 @@ impl Wordroute {
     fn try_route_word(&mut self) -> bool {
         self.try_route_buf.clear();
 
-        if let Some(start) =
-            self.word_finder.find(
+        // Try to keep the current route so that the highlighted path
+        // doesn’t jump around while typing
+        let found = match self.route_start {
+            Some(start) => self.word_finder.extend_route(
                 self.puzzle.grid(),
                 &self.word,
+                start,
+                &self.route_steps,
                 &mut self.try_route_buf,
-            )
-        {
+            ),
+            None => self.word_finder.find_partial(
+                self.puzzle.grid(),
+                &self.word,
+                &mut self.try_route_buf,
+            ),
+        };
+
+        if let Some(start) = found {
             std::mem::swap(&mut self.route_steps, &mut self.try_route_buf);
             self.route_start = Some(start);
 
@@ -1278,7 +1302,7 @@ impl Wordroute {
 
         if tile_x >= self.puzzle.width() ||
             tile_y >= self.puzzle.height() ||
-            self.puzzle.grid().at(tile_x as u32, tile_y as u32) == '.'
+            self.puzzle.grid().at(tile_x as u32, tile_y as u32) == "."
         {
             None
         } else {
@@ -1304,11 +1328,13 @@ impl Wordroute {
         if self.route_start.is_some() && self.pointer_tail.is_none() {
             self.word.pop().unwrap();
 
-            if self.route_steps.pop().is_none() {
+            if self.word.is_empty() {
+                self.route_steps.clear();
                 self.route_start = None;
             } else {
-                // Removing a character can change the route
-                // completely so let’s search for the word again
+                // Removing a character might mean that the last tile
+                // is no longer needed so fit the route to the word
+                // again
                 let try_result = self.try_route_word();
                 assert!(try_result);
             }
@@ -1357,7 +1383,7 @@ impl Wordroute {
         self.route_start = Some(position);
         self.route_steps.clear();
         self.word.clear();
-        self.word.push(self.puzzle.grid().at(position.0, position.1));
+        self.word.push_str(self.puzzle.grid().at(position.0, position.1));
         let _ = self.update_word();
     }
 
@@ -1408,19 +1434,22 @@ impl Wordroute {
             return;
         };
 
+        let topology = self.puzzle.grid().topology();
+
         // If we’re moving back a space then undo the last move
         if Some(position) == self.route_steps.last().map(|&dir| {
-            directions::reverse(last_x, last_y, dir)
+            topology.reverse(last_x, last_y, dir)
         }) {
             self.route_steps.pop().unwrap();
-            self.word.pop().unwrap();
+            let tile_length = self.puzzle.grid().at(last_x, last_y).len();
+            self.word.truncate(self.word.len() - tile_length);
             self.pointer_tail = Some(position);
             let _ = self.update_word();
         } else {
             // Can we get here from the previous position?
             let dir = 'find_direction: {
-                for dir in 0..directions::N_DIRECTIONS {
-                    if position == directions::step(last_x, last_y, dir) {
+                for dir in 0..topology.n_directions() {
+                    if position == topology.step(last_x, last_y, dir) {
                         break 'find_direction dir;
                     }
                 }
@@ -1437,11 +1466,11 @@ impl Wordroute {
                     return;
                 }
 
-                (x, y) = directions::step(x, y, dir);
+                (x, y) = topology.step(x, y, dir);
             }
 
             self.route_steps.push(dir);
-            self.word.push(self.puzzle.grid().at(position.0, position.1));
+            self.word.push_str(self.puzzle.grid().at(position.0, position.1));
             self.pointer_tail = Some(position);
             let _ = self.update_word();
         }
@@ -1483,8 +1512,12 @@ impl Wordroute {
         self.sort_word_lists = self.get_checkbox_value(SORT_HINT_CHECKBOX_ID);
         self.show_some_letters =
             self.get_checkbox_value(LETTERS_HINT_CHECKBOX_ID);
+        let near_misses =
+            self.get_checkbox_value(NEAR_MISSES_HINT_CHECKBOX_ID);
+
+        self.puzzle.set_near_misses_enabled(near_misses);
 
-        if self.sort_word_lists || self.show_some_letters {
+        if self.sort_word_lists || self.show_some_letters || near_misses {
             self.puzzle.use_hints();
         }
 
@@ -1492,26 +1525,6 @@ impl Wordroute {
     }
 }
 
-fn hexagon_path(radius: f32) -> String {
-    let mut result = String::new();
-
-    for i in 0..6 {
-        let angle = i as f32 * 2.0 * PI / 6.0;
-
-        write!(
-            &mut result,
-            "{} {} {} ",
-            if i == 0 { 'M' } else { 'L' },
-            radius * angle.sin(),
-            radius * -angle.cos(),
-        ).unwrap();
-    }
-
-    result.push('z');
-
-    result
-}
-
 fn parse_puzzles(data: Vec<u8>) -> Result<Vec<PuzzleData>, ()> {
     let Ok(data) = std::str::from_utf8(&data)
     else {
@@ -1606,7 +1619,7 @@ fn build_puzzle_list(context: &Context, puzzles: Vec<PuzzleData>) {
             &format!(
                 " – {} words",
                 puzzle.words.iter()
-                    .filter(|&&(_, word_type)| word_type == WordType::Normal)
+                    .filter(|&&(_, word_type)| word_type.is_normal())
                     .count(),
             ),
         );
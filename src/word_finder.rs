@@ -15,7 +15,6 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::grid::Grid;
-use super::directions::{self, N_DIRECTIONS};
 
 struct StackEntry {
     x: u32,
@@ -37,61 +36,138 @@ impl Finder {
         }
     }
 
-    fn find_from_position<T: Extend<u8>>(
+    // Pop entries from the stack until one is found that still has
+    // directions left to try, but never pop below the floor.
+    fn backtrack(&mut self, grid: &Grid, floor: usize) {
+        while self.stack.len() > floor {
+            let entry = self.stack.pop().unwrap();
+
+            self.visited[(entry.y * grid.width() + entry.x) as usize] = false;
+
+            if entry.next_direction < grid.topology().n_directions() {
+                self.stack.push(entry);
+                break;
+            }
+        }
+    }
+
+    // If partial is true then the last tile of the route is allowed
+    // to have more letters than are left in the word. The found
+    // callback is called with the stack for each route that is found
+    // in order. If it returns true then the search stops.
+    fn find_from_position<F>(
         &mut self,
         grid: &Grid,
         word: &str,
+        partial: bool,
         start_x: u32, start_y: u32,
-        route: &mut T,
-    ) -> bool {
+        found: &mut F,
+    ) -> bool
+        where F: FnMut(&[StackEntry]) -> bool
+    {
+        self.find_from_prefix(
+            grid,
+            word,
+            partial,
+            (start_x, start_y),
+            &[],
+            found,
+        )
+    }
+
+    // Like find_from_position but the route must begin with the
+    // given directions from the start position. Only the rest of the
+    // route is searched.
+    fn find_from_prefix<F>(
+        &mut self,
+        grid: &Grid,
+        word: &str,
+        partial: bool,
+        start: (u32, u32),
+        prefix: &[u8],
+        found: &mut F,
+    ) -> bool
+        where F: FnMut(&[StackEntry]) -> bool
+    {
+        let topology = grid.topology();
+
         self.stack.clear();
+        self.visited.clear();
+        self.visited.resize((grid.width() * grid.height()) as usize, false);
+
+        let (mut x, mut y) = start;
+        let mut word_start = 0;
+
+        for &dir in prefix {
+            if x >= grid.width() ||
+                y >= grid.height() ||
+                self.visited[(y * grid.width() + x) as usize]
+            {
+                return false;
+            }
+
+            let tile = grid.at(x, y);
+
+            if !word[word_start..].starts_with(tile) {
+                return false;
+            }
+
+            self.visited[(y * grid.width() + x) as usize] = true;
+
+            self.stack.push(StackEntry {
+                x,
+                y,
+                next_direction: dir + 1,
+                word_start,
+            });
+
+            word_start += tile.len();
+            (x, y) = topology.step(x, y, dir);
+        }
+
+        // The search never backtracks into the prefix
+        let floor = self.stack.len();
+
         self.stack.push(StackEntry {
-            x: start_x,
-            y: start_y,
+            x,
+            y,
             next_direction: 0,
-            word_start: 0,
+            word_start,
         });
 
-        self.visited.clear();
-        self.visited.resize((grid.width() * grid.height()) as usize, false);
-
-        while let Some(mut entry) = self.stack.pop() {
-            let letter = word.split_at(entry.word_start).1.chars().next();
+        while self.stack.len() > floor {
+            let mut entry = self.stack.pop().unwrap();
+            let remainder = &word[entry.word_start..];
 
             if entry.x >= grid.width() ||
                 entry.y >= grid.height() ||
                 self.visited[(entry.y * grid.width() + entry.x) as usize] ||
-                Some(grid.at(entry.x, entry.y)) != letter
+                !tile_matches(grid.at(entry.x, entry.y), remainder, partial)
             {
-                // Backtrack
-                while let Some(entry) = self.stack.pop() {
-                    self.visited[
-                        (entry.y * grid.width() + entry.x) as usize
-                    ] = false;
-
-                    if entry.next_direction < N_DIRECTIONS {
-                        self.stack.push(entry);
-                        break;
-                    }
-                }
+                self.backtrack(grid, floor);
             } else {
                 self.visited[
                     (entry.y * grid.width() + entry.x) as usize
                 ] = true;
 
-                let next_word_start =
-                    entry.word_start + letter.unwrap().len_utf8();
+                let next_word_start = entry.word_start +
+                    grid.at(entry.x, entry.y).len().min(remainder.len());
 
-                if word.split_at(next_word_start).1.is_empty() {
-                    route.extend(
-                        self.stack.iter().map(|entry| {
-                            entry.next_direction as u8 - 1
-                        })
-                    );
-                    return true;
+                if next_word_start >= word.len() {
+                    if found(&self.stack) {
+                        return true;
+                    }
+
+                    // Keep looking for other routes
+                    self.visited[
+                        (entry.y * grid.width() + entry.x) as usize
+                    ] = false;
+                    self.backtrack(grid, floor);
+
+                    continue;
                 }
 
-                let next_pos = directions::step(
+                let next_pos = topology.step(
                     entry.x,
                     entry.y,
                     entry.next_direction,
@@ -114,22 +190,144 @@ impl Finder {
         false
     }
 
-    pub fn find<T: Extend<u8>>(
+    fn find_with_partial<T: Extend<u8>>(
         &mut self,
         grid: &Grid,
         word: &str,
+        partial: bool,
         route: &mut T,
     ) -> Option<(u32, u32)> {
-        for y in 0..grid.height() {
-            for x in 0..grid.width() {
-                if self.find_from_position(grid, word, x, y, route) {
-                    return Some((x, y));
-                }
+        let mut found = |stack: &[StackEntry]| {
+            route.extend(stack.iter().map(|entry| entry.next_direction - 1));
+            true
+        };
+
+        for &(x, y) in start_positions(grid, word) {
+            if self.find_from_position(grid, word, partial, x, y, &mut found) {
+                return Some((x, y));
             }
         }
 
         None
     }
+
+    #[cfg(any(target_arch = "wasm32", test))]
+    // Like find_partial, but tries to keep as much as possible of an
+    // existing route for a word that is being edited. The route is
+    // only changed back to the point where it can no longer be made to
+    // fit the word. If none of it can be kept then this is the same as
+    // find_partial.
+    pub fn extend_route<T: Extend<u8>>(
+        &mut self,
+        grid: &Grid,
+        word: &str,
+        start: (u32, u32),
+        steps: &[u8],
+        route: &mut T,
+    ) -> Option<(u32, u32)> {
+        let mut found = |stack: &[StackEntry]| {
+            route.extend(stack.iter().map(|entry| entry.next_direction - 1));
+            true
+        };
+
+        for prefix_length in (0..=steps.len()).rev() {
+            if self.find_from_prefix(
+                grid,
+                word,
+                true,
+                start,
+                &steps[0..prefix_length],
+                &mut found,
+            ) {
+                return Some(start);
+            }
+        }
+
+        self.find_with_partial(grid, word, true, route)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    // Calls the callback with the start position and the directions
+    // of every route that can be used to spell the word. The routes
+    // are given in the same order that find would try them so the
+    // first one is always the one that find returns. The search stops
+    // early if the callback returns false.
+    pub fn for_each_route<F>(
+        &mut self,
+        grid: &Grid,
+        word: &str,
+        mut callback: F,
+    )
+        where F: FnMut((u32, u32), &[u8]) -> bool
+    {
+        let mut steps = Vec::new();
+
+        for &(x, y) in start_positions(grid, word) {
+            let mut found = |stack: &[StackEntry]| {
+                steps.clear();
+                steps.extend(
+                    stack.iter().map(|entry| entry.next_direction - 1)
+                );
+                !callback((x, y), &steps)
+            };
+
+            if self.find_from_position(grid, word, false, x, y, &mut found) {
+                return;
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    // Count the number of routes for the word, but stop once the
+    // count reaches the limit.
+    pub fn count_routes(
+        &mut self,
+        grid: &Grid,
+        word: &str,
+        limit: usize,
+    ) -> usize {
+        let mut count = 0;
+
+        self.for_each_route(grid, word, |_, _| {
+            count += 1;
+            count < limit
+        });
+
+        count
+    }
+
+    pub fn find<T: Extend<u8>>(
+        &mut self,
+        grid: &Grid,
+        word: &str,
+        route: &mut T,
+    ) -> Option<(u32, u32)> {
+        self.find_with_partial(grid, word, false, route)
+    }
+
+    #[cfg(any(target_arch = "wasm32", test))]
+    // Like find, but the word only has to match the start of the
+    // letters on the last tile. This can be used to find a route for
+    // a word that is still being typed.
+    pub fn find_partial<T: Extend<u8>>(
+        &mut self,
+        grid: &Grid,
+        word: &str,
+        route: &mut T,
+    ) -> Option<(u32, u32)> {
+        self.find_with_partial(grid, word, true, route)
+    }
+}
+
+// Only the tiles that start with the same letter as the word need to
+// be tried as the start of a route
+fn start_positions<'a>(grid: &'a Grid, word: &str) -> &'a [(u32, u32)] {
+    word.chars().next().map(|letter| grid.positions(letter)).unwrap_or(&[])
+}
+
+fn tile_matches(tile: &str, remainder: &str, partial: bool) -> bool {
+    remainder.starts_with(tile) ||
+        (partial && !remainder.is_empty() && tile.starts_with(remainder))
 }
 
 #[cfg(test)]
@@ -197,7 +395,7 @@ mod test {
     #[test]
     fn not_found() {
         let mut finder = Finder::new();
-        let grid = Grid::new("haystack").unwrap();
+        let grid = Grid::new("stack").unwrap();
         assert!(finder.find(&grid, "needle", &mut Vec::new()).is_none());
     }
 
@@ -220,4 +418,166 @@ mod test {
 
         assert!(finder.find(&grid, "𐑕𐑑𐑳𐑯𐑑𐑕", &mut steps).is_none());
     }
+
+    #[test]
+    fn multi_letter_tiles() {
+        let mut finder = Finder::new();
+        let mut steps = Vec::new();
+
+        let grid = Grid::new("𐑕 [𐑑𐑮] 𐑳 : 𐑑 [𐑯𐑑] 𐑮").unwrap();
+
+        let (x, y) = finder.find(&grid, "𐑕𐑑𐑮𐑳𐑯𐑑", &mut steps).unwrap();
+        assert_eq!((x, y), (0, 0));
+        assert_eq!(&steps, &[3, 3, 4]);
+
+        // The tile can’t be split
+        assert!(finder.find(&grid, "𐑕𐑑𐑳", &mut steps).is_none());
+        steps.clear();
+        finder.find(&grid, "𐑕𐑑", &mut steps).unwrap();
+        assert_eq!(&steps, &[5]);
+
+        // …unless a partial match is allowed for the last tile
+        steps.clear();
+        let (x, y) = finder.find_partial(&grid, "𐑕𐑑", &mut steps).unwrap();
+        assert_eq!((x, y), (0, 0));
+        assert_eq!(&steps, &[3]);
+
+        steps.clear();
+        let (x, y) =
+            finder.find_partial(&grid, "𐑳𐑯", &mut steps).unwrap();
+        assert_eq!((x, y), (2, 0));
+        assert_eq!(&steps, &[4]);
+
+        assert!(finder.find_partial(&grid, "𐑕𐑑𐑳", &mut steps).is_none());
+    }
+
+    #[test]
+    fn all_routes() {
+        let mut finder = Finder::new();
+
+        let grid = Grid::new(
+            "𐑕 𐑑 𐑳\
+            : 𐑑 𐑯 ."
+        ).unwrap();
+
+        let mut routes = Vec::new();
+
+        finder.for_each_route(&grid, "𐑕𐑑𐑯", |start, steps| {
+            routes.push((start, steps.to_vec()));
+            true
+        });
+
+        assert_eq!(
+            &routes,
+            &[((0, 0), vec![3, 5]), ((0, 0), vec![5, 3])],
+        );
+
+        // The first route is the same one that find uses
+        let mut steps = Vec::new();
+        assert_eq!(finder.find(&grid, "𐑕𐑑𐑯", &mut steps), Some((0, 0)));
+        assert_eq!(&steps, &routes[0].1);
+
+        assert_eq!(finder.count_routes(&grid, "𐑕𐑑𐑯", usize::MAX), 2);
+        assert_eq!(finder.count_routes(&grid, "𐑕𐑑𐑯", 1), 1);
+        assert_eq!(finder.count_routes(&grid, "𐑕𐑑𐑳𐑯", usize::MAX), 1);
+        assert_eq!(finder.count_routes(&grid, "𐑕𐑑𐑳𐑕", usize::MAX), 0);
+
+        // Palindromes can be spelled in both directions
+        let grid = Grid::new("𐑨 𐑚 𐑨").unwrap();
+        assert_eq!(finder.count_routes(&grid, "𐑨𐑚𐑨", usize::MAX), 2);
+        assert_eq!(finder.count_routes(&grid, "𐑨", usize::MAX), 2);
+    }
+
+    #[test]
+    fn extend_route() {
+        let mut finder = Finder::new();
+        let mut steps = Vec::new();
+
+        let grid = Grid::new(
+            "𐑕 𐑑 𐑳 𐑯\
+            : 𐑑 𐑳 . ."
+        ).unwrap();
+
+        // A fresh search would go along the top row
+        finder.find(&grid, "𐑕𐑑𐑳", &mut steps).unwrap();
+        assert_eq!(&steps, &[3, 3]);
+
+        // …but the route through the bottom row can be kept
+        steps.clear();
+        let start =
+            finder.extend_route(&grid, "𐑕𐑑𐑳", (0, 0), &[5], &mut steps);
+        assert_eq!(start, Some((0, 0)));
+        assert_eq!(&steps, &[5, 3]);
+
+        // Removing a letter keeps the rest of the route
+        steps.clear();
+        let start =
+            finder.extend_route(&grid, "𐑕𐑑", (0, 0), &[5, 3], &mut steps);
+        assert_eq!(start, Some((0, 0)));
+        assert_eq!(&steps, &[5]);
+
+        // If the route can’t be extended it backtracks as little as
+        // possible
+        steps.clear();
+        let start =
+            finder.extend_route(&grid, "𐑕𐑑𐑳𐑯", (0, 0), &[5, 3], &mut steps);
+        assert_eq!(start, Some((0, 0)));
+        assert_eq!(&steps, &[3, 3, 3]);
+
+        // If the start tile doesn’t match then it searches again
+        steps.clear();
+        let start = finder.extend_route(&grid, "𐑳𐑯", (0, 0), &[5], &mut steps);
+        assert_eq!(start, Some((2, 0)));
+        assert_eq!(&steps, &[3]);
+
+        steps.clear();
+        assert!(
+            finder.extend_route(&grid, "𐑯𐑕", (0, 0), &[5], &mut steps)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn extend_multi_letter_route() {
+        let mut finder = Finder::new();
+        let mut steps = Vec::new();
+
+        let grid = Grid::new("𐑕 𐑑 𐑮 : [𐑑𐑮] . .").unwrap();
+
+        finder.find(&grid, "𐑕𐑑𐑮", &mut steps).unwrap();
+        assert_eq!(&steps, &[3, 3]);
+
+        // The partially typed tile is kept when the rest of it is typed
+        steps.clear();
+        let start =
+            finder.extend_route(&grid, "𐑕𐑑𐑮", (0, 0), &[5], &mut steps);
+        assert_eq!(start, Some((0, 0)));
+        assert_eq!(&steps, &[5]);
+    }
+
+    #[test]
+    fn square() {
+        let mut finder = Finder::new();
+        let mut steps = Vec::new();
+
+        let grid = Grid::new(
+            "#𐑐 𐑑 𐑒\n\
+              𐑚 𐑔 𐑕\n\
+              𐑖 𐑗 𐑘"
+        ).unwrap();
+
+        let (x, y) = finder.find(&grid, "𐑐𐑔𐑘", &mut steps).unwrap();
+        assert_eq!(x, 0);
+        assert_eq!(y, 0);
+        assert_eq!(&steps, &[7, 7]);
+
+        steps.clear();
+        let (x, y) = finder.find(&grid, "𐑖𐑚𐑑𐑕", &mut steps).unwrap();
+        assert_eq!(x, 0);
+        assert_eq!(y, 2);
+        assert_eq!(&steps, &[1, 2, 7]);
+
+        // 𐑚 and 𐑒 are not adjacent on a square grid
+        assert!(finder.find(&grid, "𐑚𐑒", &mut steps).is_none());
+    }
 }